@@ -0,0 +1,107 @@
+use stage::{
+  App, AppEventHandler, AppSetupHandler, AppWindowEventHandler, Collider, CollisionEvent,
+  CommandQueue, Context, EngineError, LoadScene, RigidBody, Scene, Scenes, Transform,
+  WindowBuilder, ELWT,
+};
+
+/// An example application.
+pub struct ExampleApp;
+
+impl App for ExampleApp {}
+
+impl AppSetupHandler for ExampleApp {
+  fn window(window_builder: WindowBuilder) -> WindowBuilder {
+    window_builder
+  }
+  fn scenes(scenes: Scenes) -> Scenes {
+    scenes.register(ExampleScene)
+  }
+}
+
+impl AppEventHandler for ExampleApp {
+  fn init(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // Load the example scene.
+    command_queue.enqueue(LoadScene::<ExampleScene>);
+    Ok(())
+  }
+  fn exit(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+}
+
+impl AppWindowEventHandler for ExampleApp {
+  fn close_request(
+    &mut self,
+    elwt: &ELWT,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    elwt.exit();
+    Ok(())
+  }
+}
+
+/// An example scene.
+pub struct ExampleScene;
+
+impl Scene for ExampleScene {
+  fn load(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // Spawn a falling entity.
+    context.world.spawn_entity((
+      Transform::new([0.0, 0.0], [128.0, 128.0]),
+      RigidBody::new([0.0, 15.0]),
+      Collider::new([0.0, 0.0], [128.0, 128.0]),
+    ));
+    // Add an environment collider for it to land on.
+    context
+      .simulator
+      .add_environment_collider([0.0, 256.0], [128.0, 128.0]);
+    Ok(())
+  }
+  fn frame(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  fn postframe(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+    collision_events: Vec<CollisionEvent>,
+  ) -> Result<(), EngineError> {
+    for collision_event in collision_events {
+      println!("{:?}", collision_event);
+    }
+    Ok(())
+  }
+  fn unload(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+}
+
+fn main() -> Result<(), EngineError> {
+  // Simulate ten seconds without a window.
+  let mut context = Box::new(ExampleApp).run_headless(600)?;
+  for (_, transform) in context.world.standard_query::<&Transform>() {
+    println!("Final position: {:?}", transform.position);
+  }
+  Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::Command;
 use thiserror::Error;
 use winit::error::EventLoopError;
 
@@ -6,3 +7,14 @@ pub enum AppError {
   #[error("{0}")]
   EventLoop(#[from] EventLoopError),
 }
+
+/// Decides how the application responds to an error.
+pub enum ErrorPolicy {
+  /// Continue running.
+  Continue,
+  /// Exit the application.
+  Exit,
+  /// Enqueue a command, such as loading a fallback scene, and continue
+  /// running.
+  Fallback(Box<dyn Command>),
+}
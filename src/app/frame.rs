@@ -0,0 +1,64 @@
+use crate::{CommandQueue, Context, EngineError, Scenes};
+
+/// Execute a frame. The timer should be ticked beforehand.
+pub fn execute_frame(
+  command_queue: &mut CommandQueue,
+  scenes: &mut Scenes,
+  context: &mut Context,
+) -> Result<(), EngineError> {
+  // Replay the next recorded frame first, if replaying, so it's used up
+  // even if the frame fails, like it was when recorded.
+  let result = context
+    .input
+    .replay_frame(&mut context.timer)
+    .map_err(EngineError::from)
+    .and_then(|_| execute_frame_inner(command_queue, scenes, context));
+  // Record the frame, if recording, then end the input frame. This is done
+  // even if the frame failed, so a recording stays in sync with the fixed
+  // steps that were taken.
+  context.input.record_frame(&context.timer);
+  context.input.end_frame();
+  result
+}
+
+/// Execute a frame, without recording or replaying it.
+fn execute_frame_inner(
+  command_queue: &mut CommandQueue,
+  scenes: &mut Scenes,
+  context: &mut Context,
+) -> Result<(), EngineError> {
+  // Advance the scene transition, if any. This is done before executing
+  // the command queue, so that a transition started by a command renders
+  // (and captures) the old scene for a frame before switching.
+  scenes.update_transition(command_queue, context)?;
+  // Finish the assets of the scene preload that are ready, if any.
+  scenes.update_preload(command_queue, context)?;
+  // Execute the command queue.
+  command_queue.execute(scenes, context)?;
+  // Execute the scene frame, then apply the deferred world changes that
+  // weren't recorded by a scene.
+  scenes.frame(command_queue, context)?;
+  context.world_commands.apply(&mut context.world);
+  // Execute the simulator once per fixed step.
+  let mut collision_events = Vec::new();
+  while context.timer.step() {
+    context.world.propagate_transforms();
+    collision_events.extend(
+      context
+        .simulator
+        .execute(&mut context.world, context.timer.fixed_timestep()),
+    );
+  }
+  // Propagate the transforms, draw the physics if debug drawing is enabled,
+  // then execute the renderer.
+  context.world.propagate_transforms();
+  context
+    .simulator
+    .debug_draw(&mut context.world, context.renderer.debug_draw());
+  context.renderer.execute(&mut context.world)?;
+  // Execute the scene postframe, then apply the deferred world changes
+  // that weren't recorded by a scene.
+  scenes.postframe(command_queue, context, collision_events)?;
+  context.world_commands.apply(&mut context.world);
+  Ok(())
+}
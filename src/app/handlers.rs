@@ -1,5 +1,10 @@
-use crate::{AppError, CommandQueue, Context, EngineError, Scenes, WindowBuilder, ELWT};
+use crate::{
+  app::{frame::execute_frame, headless::Headless},
+  AppError, CommandQueue, Context, EngineError, ErrorPolicy, InputEvent, Scenes, WindowBuilder,
+  ELWT,
+};
 use glium::backend::glutin::SimpleWindowBuilder;
+use std::path::PathBuf;
 use winit::{
   event::{Event, StartCause, WindowEvent},
   event_loop::EventLoop,
@@ -43,30 +48,55 @@ pub trait App: AppSetupHandler + AppEventHandler + AppWindowEventHandler {
               // Assert that the id of the window that emitted the event
               // and the window that the user sees are the same.
               assert_eq!(window.id(), window_id);
+              // Update the input state.
+              if let Some(input_event) = InputEvent::from_window_event(&event) {
+                context.input.handle(input_event);
+              }
               // Match the window event.
               match event {
                 // Close request event.
                 WindowEvent::CloseRequested => {
                   self.close_request(elwt, &mut command_queue, &mut context)?
                 },
+                // Resize event. A size of zero means the window was
+                // minimized.
+                WindowEvent::Resized(size) => {
+                  if size.width == 0 || size.height == 0 {
+                    self.minimized(elwt, &mut command_queue, &mut context)?;
+                  } else {
+                    let size = (size.width, size.height);
+                    context.renderer.resize(size);
+                    self.resized(elwt, size, &mut command_queue, &mut context)?;
+                  }
+                },
+                // Focus gained event.
+                WindowEvent::Focused(true) => {
+                  self.focus_gained(elwt, &mut command_queue, &mut context)?
+                },
+                // Focus lost event. Release the input, since the release
+                // events won't be received while unfocused.
+                WindowEvent::Focused(false) => {
+                  context.input.release_all();
+                  self.focus_lost(elwt, &mut command_queue, &mut context)?;
+                },
+                // Scale factor change event.
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                  self.scale_factor_changed(elwt, scale_factor, &mut command_queue, &mut context)?
+                },
+                // File drop event.
+                WindowEvent::DroppedFile(path) => {
+                  self.file_dropped(elwt, path, &mut command_queue, &mut context)?
+                },
+                // Occlusion event.
+                WindowEvent::Occluded(is_occluded) => {
+                  self.occluded(elwt, is_occluded, &mut command_queue, &mut context)?
+                },
                 // Redraw request.
                 WindowEvent::RedrawRequested => {
-                  // Execute the command queue.
-                  command_queue.execute(&mut scenes, &mut context)?;
-                  // Get the scene.
-                  let scene = scenes.loaded()?;
-                  // Execute the scene frame.
-                  scene.frame(&mut command_queue, &mut context)?;
-                  // Execute the simulator.
-                  let collision_events = context.simulator.execute(
-                    &mut context.world,
-                    &mut context.renderer,
-                    1.0 / 30.0,
-                  );
-                  // Execute the renderer.
-                  context.renderer.execute(&mut context.world)?;
-                  // Execute the scene postframe.
-                  scene.postframe(&mut command_queue, &mut context, collision_events)?;
+                  // Tick the timer.
+                  context.timer.tick();
+                  // Execute the frame.
+                  execute_frame(&mut command_queue, &mut scenes, &mut context)?;
                 },
                 // Ignore other window events.
                 _ => (),
@@ -79,12 +109,27 @@ pub trait App: AppSetupHandler + AppEventHandler + AppWindowEventHandler {
           }
           Ok::<(), EngineError>(())
         })() {
-          eprintln!("{}", error);
+          // Let the application decide how to respond to the error.
+          match self.error(&error, &mut command_queue, &mut context) {
+            ErrorPolicy::Continue => (),
+            ErrorPolicy::Exit => elwt.exit(),
+            ErrorPolicy::Fallback(command) => command_queue.enqueue_boxed(command),
+          }
         }
       })
       .map_err(AppError::from)?;
     Ok(())
   }
+  /// Run the application headless for `ticks` ticks, returning the
+  /// context once finished. See `Headless` for more information.
+  fn run_headless(self: Box<Self>, ticks: u64) -> Result<Context, EngineError>
+  where
+    Self: Sized,
+  {
+    let mut headless = Headless::new(self)?;
+    headless.run(ticks)?;
+    headless.exit()
+  }
 }
 
 /// Handles application setup.
@@ -109,6 +154,17 @@ pub trait AppEventHandler {
     command_queue: &mut CommandQueue,
     context: &mut Context,
   ) -> Result<(), EngineError>;
+  /// Handle an error returned by a scene, command or event handler.
+  /// By default, the error is logged and the application continues running.
+  fn error(
+    &mut self,
+    error: &EngineError,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> ErrorPolicy {
+    log::error!("{}", error);
+    ErrorPolicy::Continue
+  }
 }
 
 /// Handles application window events.
@@ -120,4 +176,73 @@ pub trait AppWindowEventHandler {
     command_queue: &mut CommandQueue,
     context: &mut Context,
   ) -> Result<(), EngineError>;
+  /// Handle the resize event. `size` is the new inner size of the window,
+  /// in physical pixels.
+  fn resized(
+    &mut self,
+    _elwt: &ELWT,
+    _size: (u32, u32),
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the minimize event.
+  fn minimized(
+    &mut self,
+    _elwt: &ELWT,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the focus gained event.
+  fn focus_gained(
+    &mut self,
+    _elwt: &ELWT,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the focus lost event.
+  fn focus_lost(
+    &mut self,
+    _elwt: &ELWT,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the scale factor change event.
+  fn scale_factor_changed(
+    &mut self,
+    _elwt: &ELWT,
+    _scale_factor: f64,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the file drop event.
+  fn file_dropped(
+    &mut self,
+    _elwt: &ELWT,
+    _path: PathBuf,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Handle the occlusion event, sent when the window becomes fully hidden
+  /// from view, or visible again.
+  fn occluded(
+    &mut self,
+    _elwt: &ELWT,
+    _is_occluded: bool,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
 }
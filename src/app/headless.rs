@@ -0,0 +1,251 @@
+use crate::{
+  app::frame::execute_frame, App, CommandQueue, Context, EngineError, ErrorPolicy, Recording,
+  Scenes,
+};
+
+/// Runs an application without a window, display or event loop.
+/// Rendering is disabled, and every tick advances the timer by exactly one
+/// fixed timestep, so the simulator steps once per tick regardless of how
+/// fast the ticks are executed.
+pub struct Headless<A: App> {
+  /// The application.
+  app: Box<A>,
+  /// The command queue.
+  command_queue: CommandQueue,
+  /// The scene manager.
+  scenes: Scenes,
+  /// The context.
+  context: Context,
+}
+
+impl<A: App> Headless<A> {
+  /// Create a new headless runner, invoking the application init event.
+  pub fn new(mut app: Box<A>) -> Result<Self, EngineError> {
+    // Create the command queue.
+    let mut command_queue = CommandQueue::new();
+    // Create the scene manager.
+    let scenes = {
+      let scenes = Scenes::new();
+      A::scenes(scenes)
+    };
+    // Create the headless context.
+    let mut context = Context::headless()?;
+    // Invoke the application init event.
+    app.init(&mut command_queue, &mut context)?;
+    Ok(Self {
+      app: app,
+      command_queue: command_queue,
+      scenes: scenes,
+      context: context,
+    })
+  }
+  /// Execute a single tick. Errors are handled by the application's error
+  /// policy, and only returned if the policy is to exit.
+  pub fn tick(&mut self) -> Result<(), EngineError> {
+    // Tick the timer by exactly one fixed timestep.
+    self.context.timer.tick_fixed();
+    // Execute the frame.
+    if let Err(error) = execute_frame(&mut self.command_queue, &mut self.scenes, &mut self.context)
+    {
+      // Let the application decide how to respond to the error.
+      match self
+        .app
+        .error(&error, &mut self.command_queue, &mut self.context)
+      {
+        ErrorPolicy::Continue => (),
+        ErrorPolicy::Exit => return Err(error),
+        ErrorPolicy::Fallback(command) => self.command_queue.enqueue_boxed(command),
+      }
+    }
+    Ok(())
+  }
+  /// Execute `ticks` ticks.
+  pub fn run(&mut self, ticks: u64) -> Result<(), EngineError> {
+    for _ in 0..ticks {
+      self.tick()?;
+    }
+    Ok(())
+  }
+  /// Replay a recording, executing a tick per recorded frame until the
+  /// replay is finished or stopped, such as by falling out of sync.
+  /// See `Recording` for more information.
+  pub fn replay(&mut self, recording: Recording) -> Result<(), EngineError> {
+    let frames = recording.len();
+    self.context.input.start_replay(recording);
+    for _ in 0..frames {
+      if !self.context.input.is_replaying() {
+        break;
+      }
+      self.tick()?;
+    }
+    // Stop the replay in case frames are left, so it doesn't carry over to
+    // the next ticks.
+    self.context.input.stop_replay();
+    Ok(())
+  }
+  /// Get the command queue.
+  pub fn command_queue(&mut self) -> &mut CommandQueue {
+    &mut self.command_queue
+  }
+  /// Get the context.
+  pub fn context(&self) -> &Context {
+    &self.context
+  }
+  /// Get the context mutably.
+  pub fn context_mut(&mut self) -> &mut Context {
+    &mut self.context
+  }
+  /// Invoke the application exit event, returning the context.
+  pub fn exit(mut self) -> Result<Context, EngineError> {
+    self.app.exit(&mut self.command_queue, &mut self.context)?;
+    Ok(self.context)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    AppEventHandler, AppSetupHandler, AppWindowEventHandler, Collider, CollisionEvent, InputEvent,
+    Key, LoadScene, Point, RecordedFrame, RigidBody, Scene, StartRecording, Transform, Vector,
+    WindowBuilder, ELWT,
+  };
+
+  /// An application whose scene moves an entity with the input.
+  struct TestApp {
+    /// Whether to start recording on init.
+    record: bool,
+  }
+
+  impl App for TestApp {}
+
+  impl AppSetupHandler for TestApp {
+    fn window(window_builder: WindowBuilder) -> WindowBuilder {
+      window_builder
+    }
+    fn scenes(scenes: Scenes) -> Scenes {
+      scenes.register(TestScene)
+    }
+  }
+
+  impl AppEventHandler for TestApp {
+    fn init(
+      &mut self,
+      command_queue: &mut CommandQueue,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      let bindings = context.input.bindings_mut();
+      bindings.bind_action("jump", [Key::Space]);
+      bindings.bind_axis("move_x", [Key::KeyA], [Key::KeyD]);
+      command_queue.enqueue(LoadScene::<TestScene>);
+      if self.record {
+        command_queue.enqueue(StartRecording);
+      }
+      Ok(())
+    }
+    fn exit(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  impl AppWindowEventHandler for TestApp {
+    fn close_request(
+      &mut self,
+      _: &ELWT,
+      _: &mut CommandQueue,
+      _: &mut Context,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  /// A scene that moves an entity with the "move_x" axis, and makes it jump
+  /// with the "jump" action.
+  struct TestScene;
+
+  impl Scene for TestScene {
+    fn load(&mut self, _: &mut CommandQueue, context: &mut Context) -> Result<(), EngineError> {
+      let mut rigid_body = RigidBody::new([0.0, 0.0]);
+      rigid_body.acceleration = Vector::new(0.0, 0.0);
+      context.world.spawn_entity((
+        Transform::new([0.0, 0.0], [1.0, 1.0]),
+        rigid_body,
+        Collider::new([0.0, 0.0], [1.0, 1.0]),
+      ));
+      Ok(())
+    }
+    fn frame(&mut self, _: &mut CommandQueue, context: &mut Context) -> Result<(), EngineError> {
+      let move_x = context.input.axis("move_x");
+      let jump = context.input.is_action_just_pressed("jump");
+      for (_, rigid_body) in context.world.standard_query::<&mut RigidBody>() {
+        rigid_body.velocity.x = move_x * 60.0;
+        if jump {
+          rigid_body.velocity.y -= 30.0;
+        }
+      }
+      Ok(())
+    }
+    fn postframe(
+      &mut self,
+      _: &mut CommandQueue,
+      _: &mut Context,
+      _: Vec<CollisionEvent>,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn unload(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  /// Get the position of the entity.
+  fn position(headless: &mut Headless<TestApp>) -> Point {
+    let world = &mut headless.context_mut().world;
+    let query = world.standard_query::<&Transform>();
+    query.into_iter().next().unwrap().1.position
+  }
+
+  #[test]
+  fn replay_out_of_sync_finishes() {
+    let mut recording = Recording::new();
+    for tick in [0, 7, 8] {
+      recording.push_frame(RecordedFrame {
+        tick: tick,
+        steps: 1,
+        events: Vec::new(),
+      });
+    }
+    let mut headless = Headless::new(Box::new(TestApp { record: false })).unwrap();
+    headless.replay(recording).unwrap();
+    assert!(!headless.context().input.is_replaying());
+    assert_eq!(headless.context().timer.frames(), 2);
+  }
+
+  #[test]
+  fn replay_reproduces_the_recorded_world() {
+    // Record a session, pressing keys between ticks.
+    let mut headless = Headless::new(Box::new(TestApp { record: true })).unwrap();
+    let script = [
+      (2, InputEvent::KeyPressed(Key::KeyD)),
+      (5, InputEvent::KeyPressed(Key::Space)),
+      (6, InputEvent::KeyReleased(Key::Space)),
+      (9, InputEvent::KeyPressed(Key::KeyA)),
+      (12, InputEvent::KeyReleased(Key::KeyD)),
+      (15, InputEvent::KeyPressed(Key::Space)),
+    ];
+    for tick in 0..20 {
+      for (_, event) in script.iter().filter(|(at, _)| *at == tick) {
+        headless.context_mut().input.handle(*event);
+      }
+      headless.tick().unwrap();
+    }
+    let recording = headless.context_mut().input.stop_recording().unwrap();
+    let recorded = position(&mut headless);
+    assert_ne!(recorded, Point::new(0.0, 0.0));
+    // Replay it in a new session.
+    let mut headless = Headless::new(Box::new(TestApp { record: false })).unwrap();
+    headless.replay(recording).unwrap();
+    assert!(!headless.context().input.is_replaying());
+    assert_eq!(position(&mut headless), recorded);
+  }
+}
@@ -1,2 +1,4 @@
 pub mod error;
+pub mod frame;
 pub mod handlers;
+pub mod headless;
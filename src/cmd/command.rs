@@ -13,7 +13,11 @@ pub trait Command {
 
 /// Command variants.
 pub mod commands {
-  use crate::{Command, CommandQueue, Context, EngineError, Scene, Scenes};
+  use crate::{
+    AssetManifest, Binding, Command, CommandQueue, Context, EngineError, Payload, Recording, Scene,
+    Scenes, Transition,
+  };
+  use std::{marker::PhantomData, path::PathBuf};
 
   /// Load a scene.
   #[ghost::phantom]
@@ -30,4 +34,377 @@ pub mod commands {
       Ok(())
     }
   }
+
+  /// Load a scene with a payload, which is passed to `Scene::receive`.
+  pub struct LoadSceneWith<S: Scene> {
+    payload: Payload,
+    scene: PhantomData<S>,
+  }
+
+  impl<S: Scene> LoadSceneWith<S> {
+    /// Create a new load scene with payload command.
+    pub fn new<T: 'static>(payload: T) -> Self {
+      Self {
+        payload: Payload::new(payload),
+        scene: PhantomData,
+      }
+    }
+  }
+
+  impl<S: Scene> Command for LoadSceneWith<S> {
+    fn execute(
+      self: Box<Self>,
+      command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.load_with::<S>(self.payload, command_queue, context)?;
+      Ok(())
+    }
+  }
+
+  /// Transition to a scene, unloading the previous scenes.
+  pub struct TransitionTo<S: Scene> {
+    transition: Transition,
+    payload: Option<Payload>,
+    scene: PhantomData<S>,
+  }
+
+  impl<S: Scene> TransitionTo<S> {
+    /// Create a new transition command.
+    pub fn new(transition: Transition) -> Self {
+      Self {
+        transition: transition,
+        payload: None,
+        scene: PhantomData,
+      }
+    }
+    /// Load the scene with a payload, which is passed to `Scene::receive`.
+    pub fn with_payload<T: 'static>(mut self, payload: T) -> Self {
+      self.payload.replace(Payload::new(payload));
+      self
+    }
+  }
+
+  impl<S: Scene> Command for TransitionTo<S> {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.transition::<S>(self.transition, self.payload, context)?;
+      Ok(())
+    }
+  }
+
+  /// Preload a scene's assets on a worker thread, then switch to the
+  /// scene, unloading the previous scenes. The loaded scene, such as a
+  /// loading screen, receives the progress of the preload meanwhile.
+  pub struct PreloadScene<S: Scene> {
+    manifest: AssetManifest,
+    scene: PhantomData<S>,
+  }
+
+  impl<S: Scene> PreloadScene<S> {
+    /// Create a new preload scene command.
+    pub fn new(manifest: AssetManifest) -> Self {
+      Self {
+        manifest: manifest,
+        scene: PhantomData,
+      }
+    }
+  }
+
+  impl<S: Scene> Command for PreloadScene<S> {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      _context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.preload::<S>(self.manifest)?;
+      Ok(())
+    }
+  }
+
+  /// Push a scene on top of the loaded scene, pausing the loaded scene.
+  #[ghost::phantom]
+  pub struct PushScene<S: Scene>;
+
+  impl<S: Scene> Command for PushScene<S> {
+    fn execute(
+      self: Box<Self>,
+      command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.push::<S>(command_queue, context)?;
+      Ok(())
+    }
+  }
+
+  /// Push a scene with a payload, which is passed to `Scene::receive`, on
+  /// top of the loaded scene, pausing the loaded scene.
+  pub struct PushSceneWith<S: Scene> {
+    payload: Payload,
+    scene: PhantomData<S>,
+  }
+
+  impl<S: Scene> PushSceneWith<S> {
+    /// Create a new push scene with payload command.
+    pub fn new<T: 'static>(payload: T) -> Self {
+      Self {
+        payload: Payload::new(payload),
+        scene: PhantomData,
+      }
+    }
+  }
+
+  impl<S: Scene> Command for PushSceneWith<S> {
+    fn execute(
+      self: Box<Self>,
+      command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.push_with::<S>(self.payload, command_queue, context)?;
+      Ok(())
+    }
+  }
+
+  /// Pop the loaded scene, resuming the scene below it.
+  pub struct PopScene;
+
+  impl Command for PopScene {
+    fn execute(
+      self: Box<Self>,
+      command_queue: &mut CommandQueue,
+      scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      scenes.pop(command_queue, context)?;
+      Ok(())
+    }
+  }
+
+  /// Set the fixed timestep of the simulator, in seconds. It must be
+  /// positive and finite.
+  pub struct SetFixedTimestep(pub f32);
+
+  impl Command for SetFixedTimestep {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.timer.set_fixed_timestep(self.0)?;
+      Ok(())
+    }
+  }
+
+  /// Set the time scale of the simulator. 1 is real time.
+  pub struct SetTimeScale(pub f32);
+
+  impl Command for SetTimeScale {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.timer.set_time_scale(self.0);
+      Ok(())
+    }
+  }
+
+  /// Pause the simulator.
+  pub struct PauseSimulation;
+
+  impl Command for PauseSimulation {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.timer.pause();
+      Ok(())
+    }
+  }
+
+  /// Resume the simulator.
+  pub struct ResumeSimulation;
+
+  impl Command for ResumeSimulation {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.timer.resume();
+      Ok(())
+    }
+  }
+
+  /// Step the paused simulator by a number of fixed steps.
+  pub struct StepSimulation(pub u32);
+
+  impl Command for StepSimulation {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.timer.queue_steps(self.0);
+      Ok(())
+    }
+  }
+
+  /// Enable or disable debug drawing.
+  pub struct SetDebugDraw(pub bool);
+
+  impl Command for SetDebugDraw {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.renderer.debug_draw().set_enabled(self.0);
+      Ok(())
+    }
+  }
+
+  /// Toggle debug drawing.
+  pub struct ToggleDebugDraw;
+
+  impl Command for ToggleDebugDraw {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.renderer.debug_draw().toggle();
+      Ok(())
+    }
+  }
+
+  /// Rebind an action, replacing its previous bindings.
+  pub struct RebindAction {
+    pub action: String,
+    pub bindings: Vec<Binding>,
+  }
+
+  impl RebindAction {
+    /// Create a new rebind action command.
+    pub fn new(
+      action: impl ToString,
+      bindings: impl IntoIterator<Item = impl Into<Binding>>,
+    ) -> Self {
+      Self {
+        action: action.to_string(),
+        bindings: bindings.into_iter().map(Into::into).collect(),
+      }
+    }
+  }
+
+  impl Command for RebindAction {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      let bindings = context.input.bindings_mut();
+      bindings.bind_action(self.action, self.bindings);
+      Ok(())
+    }
+  }
+
+  /// Rebind an axis, replacing its previous bindings.
+  pub struct RebindAxis {
+    pub axis: String,
+    pub negative: Vec<Binding>,
+    pub positive: Vec<Binding>,
+  }
+
+  impl RebindAxis {
+    /// Create a new rebind axis command.
+    pub fn new(
+      axis: impl ToString,
+      negative: impl IntoIterator<Item = impl Into<Binding>>,
+      positive: impl IntoIterator<Item = impl Into<Binding>>,
+    ) -> Self {
+      Self {
+        axis: axis.to_string(),
+        negative: negative.into_iter().map(Into::into).collect(),
+        positive: positive.into_iter().map(Into::into).collect(),
+      }
+    }
+  }
+
+  impl Command for RebindAxis {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      let bindings = context.input.bindings_mut();
+      bindings.bind_axis(self.axis, self.negative, self.positive);
+      Ok(())
+    }
+  }
+
+  /// Start recording input.
+  pub struct StartRecording;
+
+  impl Command for StartRecording {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.input.start_recording();
+      Ok(())
+    }
+  }
+
+  /// Stop recording input, saving the recording to a file.
+  pub struct StopRecording(pub PathBuf);
+
+  impl Command for StopRecording {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      if let Some(recording) = context.input.stop_recording() {
+        recording.save(self.0)?;
+      }
+      Ok(())
+    }
+  }
+
+  /// Start replaying a recording, from the next frame.
+  pub struct StartReplay(pub Recording);
+
+  impl Command for StartReplay {
+    fn execute(
+      self: Box<Self>,
+      _command_queue: &mut CommandQueue,
+      _scenes: &mut Scenes,
+      context: &mut Context,
+    ) -> Result<(), EngineError> {
+      context.input.start_replay(self.0);
+      Ok(())
+    }
+  }
 }
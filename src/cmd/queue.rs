@@ -17,6 +17,10 @@ impl CommandQueue {
   pub fn enqueue(&mut self, command: impl Command + 'static) {
     self.queue.push_back(Box::new(command));
   }
+  /// Push a boxed command to the queue.
+  pub fn enqueue_boxed(&mut self, command: Box<dyn Command>) {
+    self.queue.push_back(command);
+  }
   /// Execute the commands in the queue.
   pub fn execute(&mut self, scenes: &mut Scenes, context: &mut Context) -> Result<(), EngineError> {
     while let Some(command) = self.queue.pop_front() {
@@ -1,4 +1,8 @@
-use crate::{Display, EngineError, Renderer, Simulator, World};
+use crate::{
+  ComponentRegistry, Display, EngineError, Input, Prefabs, Renderer, Simulator, Timer, World,
+  WorldCommands,
+};
+use std::any::TypeId;
 
 /// Holds a majority of the application's data.
 pub struct Context {
@@ -6,17 +10,50 @@ pub struct Context {
   pub renderer: Renderer,
   /// The world.
   pub world: World,
+  /// Changes to the world that are deferred.
+  pub world_commands: WorldCommands,
   /// The simulator.
   pub simulator: Simulator,
+  /// The timer.
+  pub timer: Timer,
+  /// The input state.
+  pub input: Input,
+  /// The components that can be stored in documents.
+  pub registry: ComponentRegistry,
+  /// The named prefabs.
+  pub prefabs: Prefabs,
 }
 
 impl Context {
   /// Create a new context.
   pub fn new(display: Display) -> Result<Self, EngineError> {
+    Self::with_display(Some(display))
+  }
+  /// Create a new headless context, which has no display to render to.
+  pub fn headless() -> Result<Self, EngineError> {
+    Self::with_display(None)
+  }
+  /// Create a new context, which is headless if there is no display.
+  fn with_display(display: Option<Display>) -> Result<Self, EngineError> {
+    let renderer = match display {
+      Some(display) => Renderer::new(display)?,
+      None => Renderer::headless()?,
+    };
     Ok(Self {
-      renderer: Renderer::new(display)?,
+      renderer: renderer,
       world: World::new(),
+      world_commands: WorldCommands::new(),
       simulator: Simulator::new(),
+      timer: Timer::new(),
+      input: Input::new(),
+      registry: ComponentRegistry::new(),
+      prefabs: Prefabs::new(),
     })
   }
+  /// Set the scene that owns the entities spawned and the environment
+  /// colliders added from now on, if any.
+  pub fn set_owner(&mut self, owner: Option<TypeId>) {
+    self.world.set_owner(owner);
+    self.simulator.set_owner(owner);
+  }
 }
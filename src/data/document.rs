@@ -0,0 +1,395 @@
+use crate::{ComponentRegistry, DataError, Entity, Point, Simulator, Size, StoredComponent, World};
+use hecs::EntityBuilder;
+use ron::ser::PrettyConfig;
+use rustc_hash::FxHashMap;
+use serde::{
+  de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+  Deserialize, Deserializer, Serialize,
+};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+/// An environment collider in a document.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct EnvironmentCollider {
+  pub position: Point,
+  pub size: Size,
+}
+
+/// An entity in a document, as its components by registered name.
+#[derive(Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct EntityDocument {
+  pub components: BTreeMap<String, Box<dyn StoredComponent>>,
+}
+
+impl EntityDocument {
+  /// Build the entity, cloning its components.
+  pub fn build(&self) -> EntityBuilder {
+    let mut builder = EntityBuilder::new();
+    for component in self.components.values() {
+      component.clone_boxed().add_to(&mut builder);
+    }
+    builder
+  }
+}
+
+/// Describes the entities, their hierarchy, environment colliders and
+/// active camera of a scene. Documents are stored as RON, and the
+/// components of their entities are looked up by name in a
+/// `ComponentRegistry`.
+#[derive(Clone, Default, Serialize)]
+pub struct SceneDocument {
+  /// The entities.
+  pub entities: Vec<EntityDocument>,
+  /// The environment colliders.
+  pub environment: Vec<EnvironmentCollider>,
+  /// The index of the entity that is the active camera, if any.
+  pub camera: Option<usize>,
+  /// The index of the parent of each child entity, by the index of the
+  /// child.
+  pub parents: BTreeMap<usize, usize>,
+}
+
+impl SceneDocument {
+  /// Create a new empty document.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Read a document from a RON string.
+  pub fn from_ron(source: &str, registry: &ComponentRegistry) -> Result<Self, DataError> {
+    let mut deserializer = ron::Deserializer::from_str(source)?;
+    let document = DocumentSeed(registry)
+      .deserialize(&mut deserializer)
+      .map_err(|error| deserializer.span_error(error))?;
+    deserializer
+      .end()
+      .map_err(|error| deserializer.span_error(error))?;
+    Ok(document)
+  }
+  /// Write the document to a RON string.
+  pub fn to_ron(&self) -> Result<String, DataError> {
+    Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+  }
+  /// Load a document from a RON file.
+  pub fn load(path: impl AsRef<Path>, registry: &ComponentRegistry) -> Result<Self, DataError> {
+    Self::from_ron(&fs::read_to_string(path)?, registry)
+  }
+  /// Save the document to a RON file.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DataError> {
+    fs::write(path, self.to_ron()?)?;
+    Ok(())
+  }
+  /// Capture the entities of a world that have registered components, their
+  /// hierarchy, and the environment colliders of a simulator. Children whose
+  /// parent isn't captured are captured as root entities.
+  pub fn capture(registry: &ComponentRegistry, world: &World, simulator: &Simulator) -> Self {
+    // Capture the entities, keeping track of the active camera and their
+    // indices.
+    let active_camera = world.actives.camera().ok();
+    let mut document = Self::new();
+    let mut indices = FxHashMap::default();
+    for entity in world.entities() {
+      let components = registry.capture(world, entity);
+      if components.is_empty() {
+        continue;
+      }
+      if active_camera == Some(entity) {
+        document.camera.replace(document.entities.len());
+      }
+      indices.insert(entity, document.entities.len());
+      document.entities.push(EntityDocument {
+        components: components,
+      });
+    }
+    // Capture the parents of the captured entities.
+    for (&entity, &index) in &indices {
+      let parent = world.parent(entity).and_then(|parent| indices.get(&parent));
+      if let Some(&parent) = parent {
+        document.parents.insert(index, parent);
+      }
+    }
+    // Capture the environment colliders.
+    document.environment = simulator
+      .environment_colliders()
+      .map(|(position, size)| EnvironmentCollider {
+        position: position,
+        size: size,
+      })
+      .collect();
+    document
+  }
+  /// Spawn the entities and add the environment colliders of the document,
+  /// parenting the entities and setting the active camera if there is one.
+  /// The document can be spawned more than once.
+  /// Returns the spawned entities, in the order of the document.
+  pub fn spawn(
+    &self,
+    world: &mut World,
+    simulator: &mut Simulator,
+  ) -> Result<Vec<Entity>, DataError> {
+    // Check the camera and hierarchy first, so nothing is spawned if they
+    // are invalid.
+    if let Some(camera) = self.camera {
+      if camera >= self.entities.len() {
+        Err(DataError::EntityNotFound(camera))?;
+      }
+    }
+    self.check_parents()?;
+    // Spawn the entities.
+    let entities = self
+      .entities
+      .iter()
+      .map(|entity| world.spawn_entity(entity.build().build()))
+      .collect::<Vec<_>>();
+    // Add the environment colliders.
+    for collider in &self.environment {
+      simulator.add_environment_collider(collider.position, collider.size);
+    }
+    // Parent the entities. The hierarchy was checked, so this can't fail.
+    for (&child, &parent) in &self.parents {
+      let _ = world.set_parent(entities[child], entities[parent]);
+    }
+    // Set the active camera.
+    if let Some(camera) = self.camera {
+      world.actives.set_camera(entities[camera]);
+    }
+    Ok(entities)
+  }
+  /// Check that the parents of the document are entities of it, and that no
+  /// entity is its own ancestor.
+  fn check_parents(&self) -> Result<(), DataError> {
+    for (&child, &parent) in &self.parents {
+      for index in [child, parent] {
+        if index >= self.entities.len() {
+          Err(DataError::EntityNotFound(index))?;
+        }
+      }
+      // Walk up the ancestors, which can't take more steps than there are
+      // parents unless there is a cycle.
+      let mut ancestor = Some(parent);
+      for _ in 0..self.parents.len() {
+        let Some(entity) = ancestor else {
+          break;
+        };
+        if entity == child {
+          Err(DataError::HierarchyCycle(child))?;
+        }
+        ancestor = self.parents.get(&entity).copied();
+      }
+    }
+    Ok(())
+  }
+}
+
+/// The fields of a document.
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum DocumentField {
+  Entities,
+  Environment,
+  Camera,
+  Parents,
+}
+
+/// Deserializes a document, looking its components up in a registry.
+struct DocumentSeed<'a>(&'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for DocumentSeed<'_> {
+  type Value = SceneDocument;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    const FIELDS: &[&str] = &["entities", "environment", "camera", "parents"];
+    deserializer.deserialize_struct("SceneDocument", FIELDS, self)
+  }
+}
+
+impl<'de> Visitor<'de> for DocumentSeed<'_> {
+  type Value = SceneDocument;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a scene document")
+  }
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+    // Every field is optional.
+    let mut document = SceneDocument::new();
+    while let Some(field) = map.next_key()? {
+      match field {
+        DocumentField::Entities => document.entities = map.next_value_seed(EntitiesSeed(self.0))?,
+        DocumentField::Environment => document.environment = map.next_value()?,
+        DocumentField::Camera => document.camera = map.next_value()?,
+        DocumentField::Parents => document.parents = map.next_value()?,
+      }
+    }
+    Ok(document)
+  }
+}
+
+/// Deserializes the entities of a document.
+struct EntitiesSeed<'a>(&'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for EntitiesSeed<'_> {
+  type Value = Vec<EntityDocument>;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_seq(self)
+  }
+}
+
+impl<'de> Visitor<'de> for EntitiesSeed<'_> {
+  type Value = Vec<EntityDocument>;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a list of entities")
+  }
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    let mut entities = Vec::new();
+    while let Some(entity) = seq.next_element_seed(EntitySeed(self.0))? {
+      entities.push(entity);
+    }
+    Ok(entities)
+  }
+}
+
+/// Deserializes an entity of a document.
+pub(crate) struct EntitySeed<'a>(pub(crate) &'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_> {
+  type Value = EntityDocument;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_map(self)
+  }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_> {
+  type Value = EntityDocument;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a map of component names to components")
+  }
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+    let mut entity = EntityDocument::default();
+    while let Some(name) = map.next_key::<String>()? {
+      let component = map.next_value_seed(ComponentSeed {
+        registry: self.0,
+        name: &name,
+      })?;
+      entity.components.insert(name, component);
+    }
+    Ok(entity)
+  }
+}
+
+/// Deserializes a component of an entity, by its registered name.
+struct ComponentSeed<'a> {
+  registry: &'a ComponentRegistry,
+  name: &'a str,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_> {
+  type Value = Box<dyn StoredComponent>;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+    match self.registry.deserialize(self.name, &mut deserializer) {
+      Some(result) => result.map_err(de::Error::custom),
+      None => Err(de::Error::custom(format!(
+        "Component {} is not registered",
+        self.name
+      ))),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Camera, Collider, Parent, Persistent, Transform};
+
+  #[test]
+  fn documents_round_trip() {
+    let registry = ComponentRegistry::new();
+    let mut world = World::new();
+    let mut simulator = Simulator::new();
+    world.spawn_entity((
+      Transform::new([1.0, 2.0], [3.0, 4.0]).with_rotation(0.5),
+      Collider::new([0.0, 0.0], [8.0, 8.0]),
+      Persistent,
+    ));
+    let camera = world.spawn_entity((
+      Transform::new([0.0, 0.0], [1.0, 1.0]),
+      Camera::new([0.0, 0.0]),
+    ));
+    world.actives.set_camera(camera);
+    simulator.add_environment_collider([10.0, 20.0], [30.0, 40.0]);
+    let ron = SceneDocument::capture(&registry, &world, &simulator)
+      .to_ron()
+      .unwrap();
+    let document = SceneDocument::from_ron(&ron, &registry).unwrap();
+    assert_eq!(document.entities.len(), 2);
+    assert_eq!(document.to_ron().unwrap(), ron);
+    // Spawn the document, and check it captures the same.
+    let mut world = World::new();
+    let mut simulator = Simulator::new();
+    let entities = document.spawn(&mut world, &mut simulator).unwrap();
+    let camera = world.actives.camera().unwrap();
+    assert!(world.standard_inspect::<&Camera>(camera).is_ok());
+    let body = entities
+      .into_iter()
+      .find(|entity| *entity != camera)
+      .unwrap();
+    let (transform, _, _) = world
+      .standard_inspect::<(&Transform, &Collider, &Persistent)>(body)
+      .unwrap();
+    assert_eq!(transform.position, Point::new(1.0, 2.0));
+    assert_eq!(transform.rotation, 0.5);
+    let environment = simulator.environment_colliders().collect::<Vec<_>>();
+    assert_eq!(
+      environment,
+      vec![(Point::new(10.0, 20.0), Size::new(30.0, 40.0))]
+    );
+    let recaptured = SceneDocument::capture(&registry, &world, &simulator);
+    assert_eq!(recaptured.to_ron().unwrap(), ron);
+  }
+
+  #[test]
+  fn unregistered_components_are_rejected() {
+    let registry = ComponentRegistry::empty();
+    let source = r#"(entities: [{"Transform": (position: (x: 0.0, y: 0.0), scale: (1.0, 1.0))}])"#;
+    assert!(matches!(
+      SceneDocument::from_ron(source, &registry),
+      Err(DataError::Deserialize(_))
+    ));
+    // Documents with missing fields are empty.
+    let document = SceneDocument::from_ron("()", &registry).unwrap();
+    assert!(document.entities.is_empty());
+    assert!(document.camera.is_none());
+  }
+
+  #[test]
+  fn hierarchy_round_trips() {
+    let registry = ComponentRegistry::new();
+    let mut world = World::new();
+    let parent = world.spawn_entity((Transform::new([10.0, 20.0], [1.0, 1.0]),));
+    world.spawn_entity((Transform::new([5.0, 0.0], [1.0, 1.0]), Parent::new(parent)));
+    let document = SceneDocument::capture(&registry, &world, &Simulator::new());
+    let ron = document.to_ron().unwrap();
+    let document = SceneDocument::from_ron(&ron, &registry).unwrap();
+    let mut world = World::new();
+    let entities = document.spawn(&mut world, &mut Simulator::new()).unwrap();
+    let (parents, children): (Vec<Entity>, Vec<Entity>) = entities
+      .into_iter()
+      .partition(|entity| world.parent(*entity).is_none());
+    assert_eq!(parents.len(), 1);
+    assert_eq!(children.len(), 1);
+    assert_eq!(world.parent(children[0]), Some(parents[0]));
+  }
+
+  #[test]
+  fn invalid_hierarchies_spawn_nothing() {
+    let registry = ComponentRegistry::new();
+    let source = "(entities: [{}, {}], parents: {0: 1, 1: 0})";
+    let document = SceneDocument::from_ron(source, &registry).unwrap();
+    let mut world = World::new();
+    let result = document.spawn(&mut world, &mut Simulator::new());
+    assert!(matches!(result, Err(DataError::HierarchyCycle(_))));
+    let source = "(entities: [{}], parents: {0: 1})";
+    let document = SceneDocument::from_ron(source, &registry).unwrap();
+    let result = document.spawn(&mut world, &mut Simulator::new());
+    assert!(matches!(result, Err(DataError::EntityNotFound(1))));
+    assert!(world.is_empty());
+  }
+}
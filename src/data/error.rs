@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+/// Data-related errors.
+#[derive(Error, Debug)]
+pub enum DataError {
+  #[error("{0}")]
+  Io(#[from] std::io::Error),
+  #[error("{0}")]
+  Deserialize(#[from] ron::error::SpannedError),
+  #[error("{0}")]
+  Serialize(#[from] ron::Error),
+  #[error("Entity {0} was not found in the document")]
+  EntityNotFound(usize),
+  #[error("Entity {0} is its own ancestor in the document")]
+  HierarchyCycle(usize),
+  #[error("Prefab {0} was not found")]
+  PrefabNotFound(String),
+  #[error("Prefab is missing component {0}")]
+  ComponentNotFound(&'static str),
+}
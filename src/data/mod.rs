@@ -0,0 +1,4 @@
+pub mod document;
+pub mod error;
+pub mod prefab;
+pub mod registry;
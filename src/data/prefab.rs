@@ -0,0 +1,527 @@
+use crate::{
+  data::document::EntitySeed, Color, Component, ComponentRegistry, DataError, Entity,
+  EntityDocument, Mesh, Point, Renderable, Scale, Texture, Transform, World,
+};
+use ahash::AHashMap;
+use hecs::EntityBuilder;
+use serde::{
+  de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+  Deserialize, Deserializer,
+};
+use std::{
+  any::{Any, TypeId},
+  fmt, fs,
+  path::Path,
+};
+
+/// A component of a prefab.
+pub trait PrefabComponent {
+  /// Add a clone of the component to an entity that is being built.
+  fn add_to(&self, builder: &mut EntityBuilder);
+  /// Clone the component.
+  fn clone_boxed(&self) -> Box<dyn PrefabComponent>;
+  /// Get the component as `Any`.
+  fn as_any(&self) -> &dyn Any;
+  /// Get the component as mutable `Any`.
+  fn as_any_mut(&mut self) -> &mut dyn Any;
+  /// Convert the component into boxed `Any`.
+  fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Component + Clone + 'static> PrefabComponent for T {
+  fn add_to(&self, builder: &mut EntityBuilder) {
+    builder.add(self.clone());
+  }
+  fn clone_boxed(&self) -> Box<dyn PrefabComponent> {
+    Box::new(self.clone())
+  }
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+  fn as_any_mut(&mut self) -> &mut dyn Any {
+    self
+  }
+  fn into_any(self: Box<Self>) -> Box<dyn Any> {
+    self
+  }
+}
+
+/// A template of an entity, which is cloned whenever it's spawned.
+/// Prefabs are defined in code, or loaded from RON files into `Prefabs`.
+#[derive(Default)]
+pub struct Prefab {
+  /// The components, at most one of each type.
+  components: Vec<Box<dyn PrefabComponent>>,
+  /// The prefabs spawned along with the prefab.
+  children: Vec<Prefab>,
+}
+
+impl Clone for Prefab {
+  fn clone(&self) -> Self {
+    Self {
+      components: self
+        .components
+        .iter()
+        .map(|component| component.clone_boxed())
+        .collect(),
+      children: self.children.clone(),
+    }
+  }
+}
+
+impl From<EntityDocument> for Prefab {
+  fn from(entity: EntityDocument) -> Self {
+    let mut prefab = Self::new();
+    for component in entity.components.into_values() {
+      prefab.insert_boxed(component.into_prefab());
+    }
+    prefab
+  }
+}
+
+impl Prefab {
+  /// Create a new prefab without components.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// A point, which is a small circle centered on its position.
+  pub fn point() -> Self {
+    let mesh = Mesh::circle(0.5, 16);
+    Self::new()
+      .with(Transform::new([0.0, 0.0], [8.0, 8.0]))
+      .with(Renderable::new(Color::none(), Texture::none(), mesh))
+  }
+  /// A square, whose origin is its top-left corner.
+  pub fn square() -> Self {
+    Self::new()
+      .with(Transform::new([0.0, 0.0], [1.0, 1.0]))
+      .with(Renderable::new(
+        Color::none(),
+        Texture::none(),
+        Mesh::square(),
+      ))
+  }
+  /// Add a component, replacing the component of the same type.
+  pub fn with<T: Component + Clone + 'static>(mut self, component: T) -> Self {
+    self.insert(component);
+    self
+  }
+  /// Add a child prefab, which is spawned along with the prefab.
+  pub fn with_child(mut self, child: Prefab) -> Self {
+    self.children.push(child);
+    self
+  }
+  /// Insert a component, replacing the component of the same type.
+  pub fn insert<T: Component + Clone + 'static>(&mut self, component: T) {
+    self.insert_boxed(Box::new(component));
+  }
+  /// Insert a boxed component, replacing the component of the same type.
+  pub fn insert_boxed(&mut self, component: Box<dyn PrefabComponent>) {
+    let type_id = component.as_any().type_id();
+    self
+      .components
+      .retain(|other| other.as_any().type_id() != type_id);
+    self.components.push(component);
+  }
+  /// Get a component.
+  pub fn get<T: 'static>(&self) -> Option<&T> {
+    self
+      .components
+      .iter()
+      .find_map(|component| component.as_any().downcast_ref())
+  }
+  /// Get a component mutably.
+  pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+    self
+      .components
+      .iter_mut()
+      .find_map(|component| component.as_any_mut().downcast_mut())
+  }
+  /// Remove a component, returning it.
+  pub fn take<T: 'static>(&mut self) -> Option<T> {
+    let index = self
+      .components
+      .iter()
+      .position(|component| component.as_any().type_id() == TypeId::of::<T>())?;
+    let component = self.components.swap_remove(index).into_any();
+    component.downcast().ok().map(|component| *component)
+  }
+  /// Get the child prefabs.
+  pub fn children(&self) -> &[Prefab] {
+    &self.children
+  }
+  /// Clone the prefab, applying overrides to it. The children are cloned
+  /// as they are.
+  pub fn instantiate(&self, overrides: PrefabOverrides) -> Self {
+    let mut prefab = self.clone();
+    overrides.apply(&mut prefab);
+    prefab
+  }
+  /// Spawn the prefab and its children, applying overrides to the prefab.
+  /// The children are parented to the prefab's entity, so they're
+  /// positioned relative to it and despawned along with it.
+  /// Returns the prefab's entity.
+  pub fn spawn(&self, overrides: PrefabOverrides, world: &mut World) -> Entity {
+    self.instantiate(overrides).spawn_tree(world)
+  }
+  /// Spawn the prefab and its children, parenting the children to it.
+  fn spawn_tree(&self, world: &mut World) -> Entity {
+    // Build the entity.
+    let mut builder = EntityBuilder::new();
+    for component in &self.components {
+      component.add_to(&mut builder);
+    }
+    let entity = world.spawn_entity(builder.build());
+    // Spawn the children, parenting them to the entity. This can't fail,
+    // since both entities were just spawned.
+    for child in &self.children {
+      let child = child.spawn_tree(world);
+      let _ = world.set_parent(child, entity);
+    }
+    entity
+  }
+}
+
+/// Overrides applied to a prefab when it's spawned.
+#[derive(Default)]
+pub struct PrefabOverrides {
+  /// The position of the prefab's transform.
+  position: Option<Point>,
+  /// The scale of the prefab's transform.
+  scale: Option<Scale>,
+  /// The rotation of the prefab's transform.
+  rotation: Option<f32>,
+  /// The color of the prefab's renderable.
+  color: Option<Color>,
+  /// Components added to the prefab, replacing those of the same type.
+  components: Vec<Box<dyn PrefabComponent>>,
+}
+
+impl PrefabOverrides {
+  /// Create new, empty overrides.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Override the position of the prefab's transform. If the prefab has no
+  /// transform, one is added with a scale of 1.
+  pub fn position(mut self, position: impl Into<Point>) -> Self {
+    self.position.replace(position.into());
+    self
+  }
+  /// Override the scale of the prefab's transform. If the prefab has no
+  /// transform, one is added at the origin.
+  pub fn scale(mut self, scale: impl Into<Scale>) -> Self {
+    self.scale.replace(scale.into());
+    self
+  }
+  /// Override the rotation of the prefab's transform, in radians. If the
+  /// prefab has no transform, one is added at the origin with a scale of 1.
+  pub fn rotation(mut self, rotation: f32) -> Self {
+    self.rotation.replace(rotation);
+    self
+  }
+  /// Override the color of the prefab's renderable. This has no effect if
+  /// the prefab has no renderable.
+  pub fn color(mut self, color: Color) -> Self {
+    self.color.replace(color);
+    self
+  }
+  /// Add a component to the prefab, replacing the component of the same
+  /// type.
+  pub fn with<T: Component + Clone + 'static>(mut self, component: T) -> Self {
+    self.components.push(Box::new(component));
+    self
+  }
+  /// Apply the overrides to a prefab.
+  fn apply(self, prefab: &mut Prefab) {
+    // Add the components first, so the other overrides apply to them.
+    for component in self.components {
+      prefab.insert_boxed(component);
+    }
+    // Override the transform.
+    if self.position.is_some() || self.scale.is_some() || self.rotation.is_some() {
+      if prefab.get::<Transform>().is_none() {
+        prefab.insert(Transform::new([0.0, 0.0], [1.0, 1.0]));
+      }
+      if let Some(transform) = prefab.get_mut::<Transform>() {
+        if let Some(position) = self.position {
+          transform.position = position;
+        }
+        if let Some(scale) = self.scale {
+          transform.scale = scale;
+        }
+        if let Some(rotation) = self.rotation {
+          transform.rotation = rotation;
+        }
+      }
+    }
+    // Override the renderable.
+    if let (Some(color), Some(renderable)) = (self.color, prefab.get_mut::<Renderable>()) {
+      renderable.color = color;
+    }
+  }
+}
+
+/// Manages named prefabs. The built-in `point` and `square` prefabs are
+/// added by default.
+pub struct Prefabs {
+  prefabs: AHashMap<String, Prefab>,
+}
+
+impl Default for Prefabs {
+  fn default() -> Self {
+    let mut prefabs = Self {
+      prefabs: AHashMap::new(),
+    };
+    prefabs.add("point", Prefab::point());
+    prefabs.add("square", Prefab::square());
+    prefabs
+  }
+}
+
+impl Prefabs {
+  /// Create a new prefab manager with the built-in prefabs.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Add a prefab, replacing the prefab previously added under its name.
+  pub fn add(&mut self, name: impl ToString, prefab: Prefab) {
+    self.prefabs.insert(name.to_string(), prefab);
+  }
+  /// Remove a prefab, returning it.
+  pub fn remove(&mut self, name: &str) -> Option<Prefab> {
+    self.prefabs.remove(name)
+  }
+  /// Get a prefab.
+  pub fn get(&self, name: &str) -> Result<&Prefab, DataError> {
+    self
+      .prefabs
+      .get(name)
+      .ok_or_else(|| DataError::PrefabNotFound(name.to_string()))
+  }
+  /// Spawn a prefab and its children, applying overrides to the prefab.
+  /// Returns the prefab's entity.
+  pub fn spawn(
+    &self,
+    name: &str,
+    overrides: PrefabOverrides,
+    world: &mut World,
+  ) -> Result<Entity, DataError> {
+    Ok(self.get(name)?.spawn(overrides, world))
+  }
+  /// Add the prefabs of a RON string, which maps names to prefabs. Their
+  /// components are looked up by name in a registry.
+  pub fn add_from_ron(
+    &mut self,
+    source: &str,
+    registry: &ComponentRegistry,
+  ) -> Result<(), DataError> {
+    let mut deserializer = ron::Deserializer::from_str(source)?;
+    let prefabs = PrefabsSeed(registry)
+      .deserialize(&mut deserializer)
+      .map_err(|error| deserializer.span_error(error))?;
+    deserializer
+      .end()
+      .map_err(|error| deserializer.span_error(error))?;
+    self.prefabs.extend(prefabs);
+    Ok(())
+  }
+  /// Add the prefabs of a RON file.
+  pub fn add_from_file(
+    &mut self,
+    path: impl AsRef<Path>,
+    registry: &ComponentRegistry,
+  ) -> Result<(), DataError> {
+    self.add_from_ron(&fs::read_to_string(path)?, registry)
+  }
+}
+
+/// The fields of a prefab.
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum PrefabField {
+  Components,
+  Children,
+}
+
+/// Deserializes named prefabs.
+struct PrefabsSeed<'a>(&'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for PrefabsSeed<'_> {
+  type Value = Vec<(String, Prefab)>;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_map(self)
+  }
+}
+
+impl<'de> Visitor<'de> for PrefabsSeed<'_> {
+  type Value = Vec<(String, Prefab)>;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a map of names to prefabs")
+  }
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+    let mut prefabs = Vec::new();
+    while let Some(name) = map.next_key::<String>()? {
+      let prefab = map.next_value_seed(PrefabSeed(self.0))?;
+      prefabs.push((name, prefab));
+    }
+    Ok(prefabs)
+  }
+}
+
+/// Deserializes a prefab.
+struct PrefabSeed<'a>(&'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for PrefabSeed<'_> {
+  type Value = Prefab;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    const FIELDS: &[&str] = &["components", "children"];
+    deserializer.deserialize_struct("Prefab", FIELDS, self)
+  }
+}
+
+impl<'de> Visitor<'de> for PrefabSeed<'_> {
+  type Value = Prefab;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a prefab")
+  }
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+    // Every field is optional.
+    let mut prefab = Prefab::new();
+    while let Some(field) = map.next_key()? {
+      match field {
+        PrefabField::Components => {
+          let entity = map.next_value_seed(EntitySeed(self.0))?;
+          for component in Prefab::from(entity).components {
+            prefab.insert_boxed(component);
+          }
+        },
+        PrefabField::Children => prefab.children = map.next_value_seed(ChildrenSeed(self.0))?,
+      }
+    }
+    Ok(prefab)
+  }
+}
+
+/// Deserializes the children of a prefab.
+struct ChildrenSeed<'a>(&'a ComponentRegistry);
+
+impl<'de> DeserializeSeed<'de> for ChildrenSeed<'_> {
+  type Value = Vec<Prefab>;
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_seq(self)
+  }
+}
+
+impl<'de> Visitor<'de> for ChildrenSeed<'_> {
+  type Value = Vec<Prefab>;
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("a list of prefabs")
+  }
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    let mut children = Vec::new();
+    while let Some(child) = seq.next_element_seed(PrefabSeed(self.0))? {
+      children.push(child);
+    }
+    Ok(children)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Collider, Parent, RenderRequest};
+
+  #[test]
+  fn overrides_apply_to_a_clone() {
+    let prefab = Prefab::square();
+    let instance = prefab.instantiate(
+      PrefabOverrides::new()
+        .position([1.0, 2.0])
+        .scale([3.0, 4.0])
+        .rotation(0.5)
+        .color(Color::red()),
+    );
+    let transform = instance.get::<Transform>().unwrap();
+    assert_eq!(transform.position, Point::new(1.0, 2.0));
+    assert_eq!(transform.scale, Scale::new(3.0, 4.0));
+    assert_eq!(transform.rotation, 0.5);
+    let color: [f32; 4] = instance.get::<Renderable>().unwrap().color.into();
+    assert_eq!(color, <[f32; 4]>::from(Color::red()));
+    // The prefab itself is left as it was.
+    let transform = prefab.get::<Transform>().unwrap();
+    assert_eq!(transform.position, Point::new(0.0, 0.0));
+    assert_eq!(transform.rotation, 0.0);
+  }
+
+  #[test]
+  fn transform_overrides_add_a_transform() {
+    let instance = Prefab::new().instantiate(PrefabOverrides::new().position([5.0, 6.0]));
+    let transform = instance.get::<Transform>().unwrap();
+    assert_eq!(transform.position, Point::new(5.0, 6.0));
+    assert_eq!(transform.scale, Scale::identity());
+    // Without a renderable, the color override has no effect.
+    let instance = Prefab::new().instantiate(PrefabOverrides::new().color(Color::red()));
+    assert!(instance.get::<Renderable>().is_none());
+    assert!(instance.get::<Transform>().is_none());
+  }
+
+  #[test]
+  fn component_overrides_replace_before_the_others_apply() {
+    let instance = Prefab::square().instantiate(
+      PrefabOverrides::new()
+        .with(Transform::new([7.0, 8.0], [2.0, 2.0]))
+        .with(Collider::new([0.0, 0.0], [1.0, 1.0]))
+        .rotation(1.0),
+    );
+    let transform = instance.get::<Transform>().unwrap();
+    assert_eq!(transform.position, Point::new(7.0, 8.0));
+    assert_eq!(transform.scale, Scale::new(2.0, 2.0));
+    assert_eq!(transform.rotation, 1.0);
+    assert!(instance.get::<Collider>().is_some());
+  }
+
+  #[test]
+  fn overrides_apply_to_the_root_only() {
+    let registry = ComponentRegistry::new();
+    let source = r#"{
+      "turret": (
+        components: {"Transform": (position: (x: 0.0, y: 0.0), scale: (1.0, 1.0))},
+        children: [
+          (components: {"Transform": (position: (x: 4.0, y: 0.0), scale: (1.0, 1.0))}),
+        ],
+      ),
+    }"#;
+    let mut prefabs = Prefabs::new();
+    prefabs.add_from_ron(source, &registry).unwrap();
+    let mut world = World::new();
+    let overrides = PrefabOverrides::new().position([10.0, 10.0]);
+    let root = prefabs.spawn("turret", overrides, &mut world).unwrap();
+    let children = world.children(root);
+    assert_eq!(children.len(), 1);
+    let (transform, parent) = world
+      .standard_inspect::<(&Transform, &Parent)>(children[0])
+      .unwrap();
+    assert_eq!(transform.position, Point::new(4.0, 0.0));
+    assert_eq!(parent.entity, root);
+    assert!(matches!(
+      prefabs.spawn("missing", PrefabOverrides::new(), &mut world),
+      Err(DataError::PrefabNotFound(_))
+    ));
+  }
+
+  #[test]
+  fn render_requests_need_a_transform_and_renderable() {
+    let (transform, renderable) = RenderRequest::point([3.0, 4.0], Color::red());
+    assert_eq!(transform.position, Point::new(3.0, 4.0));
+    assert_eq!(transform.scale, Scale::new(8.0, 8.0));
+    let color: [f32; 4] = renderable.color.into();
+    assert_eq!(color, <[f32; 4]>::from(Color::red()));
+    let prefab = Prefab::new().with(Transform::new([0.0, 0.0], [1.0, 1.0]));
+    let result = RenderRequest::prefab(&prefab, PrefabOverrides::new());
+    assert!(matches!(
+      result,
+      Err(DataError::ComponentNotFound(name)) if name.ends_with("Renderable")
+    ));
+  }
+}
@@ -0,0 +1,133 @@
+use crate::{
+  Camera, Collider, Component, Entity, Persistent, PrefabComponent, Renderable, RigidBody, Text,
+  Transform, World,
+};
+use hecs::EntityBuilder;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+
+/// A component that is stored outside of a world, such as in a document.
+pub trait StoredComponent: erased_serde::Serialize {
+  /// Add the component to an entity that is being built.
+  fn add_to(self: Box<Self>, builder: &mut EntityBuilder);
+  /// Clone the component.
+  fn clone_boxed(&self) -> Box<dyn StoredComponent>;
+  /// Convert the component into a prefab component.
+  fn into_prefab(self: Box<Self>) -> Box<dyn PrefabComponent>;
+}
+
+impl<T: Component + Clone + Serialize + 'static> StoredComponent for T {
+  fn add_to(self: Box<Self>, builder: &mut EntityBuilder) {
+    builder.add(*self);
+  }
+  fn clone_boxed(&self) -> Box<dyn StoredComponent> {
+    Box::new(self.clone())
+  }
+  fn into_prefab(self: Box<Self>) -> Box<dyn PrefabComponent> {
+    self
+  }
+}
+
+erased_serde::serialize_trait_object!(StoredComponent);
+
+impl Clone for Box<dyn StoredComponent> {
+  fn clone(&self) -> Self {
+    self.clone_boxed()
+  }
+}
+
+/// Deserializes a registered component.
+type DeserializeFn =
+  fn(&mut dyn erased_serde::Deserializer) -> Result<Box<dyn StoredComponent>, erased_serde::Error>;
+
+/// Clones a registered component out of an entity, if it has one.
+type CaptureFn = fn(&World, Entity) -> Option<Box<dyn StoredComponent>>;
+
+/// The functions of a registered component.
+#[derive(Clone, Copy)]
+struct Registration {
+  deserialize: DeserializeFn,
+  capture: CaptureFn,
+}
+
+/// Maps component names to the component types that are stored in
+/// documents. The built-in components are registered by default.
+#[derive(Clone)]
+pub struct ComponentRegistry {
+  registrations: BTreeMap<String, Registration>,
+}
+
+impl Default for ComponentRegistry {
+  fn default() -> Self {
+    let mut registry = Self::empty();
+    registry.register::<Transform>("Transform");
+    registry.register::<Renderable>("Renderable");
+    registry.register::<RigidBody>("RigidBody");
+    registry.register::<Collider>("Collider");
+    registry.register::<Camera>("Camera");
+    registry.register::<Persistent>("Persistent");
+    registry.register::<Text>("Text");
+    registry
+  }
+}
+
+impl ComponentRegistry {
+  /// Create a new component registry with the built-in components.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Create a new component registry without any components.
+  pub fn empty() -> Self {
+    Self {
+      registrations: BTreeMap::new(),
+    }
+  }
+  /// Register a component type under a name, replacing the type previously
+  /// registered under it.
+  pub fn register<T>(&mut self, name: impl ToString)
+  where
+    T: Component + Clone + Serialize + DeserializeOwned + 'static,
+  {
+    let registration = Registration {
+      deserialize: |deserializer| {
+        let component: T = erased_serde::deserialize(deserializer)?;
+        Ok(Box::new(component))
+      },
+      capture: |world, entity| {
+        let mut query = world.dynamic_inspect::<&T>(entity).ok()?;
+        let component = query.get()?.clone();
+        Some(Box::new(component))
+      },
+    };
+    self.registrations.insert(name.to_string(), registration);
+  }
+  /// Get whether a component is registered under a name.
+  pub fn contains(&self, name: &str) -> bool {
+    self.registrations.contains_key(name)
+  }
+  /// Deserialize the component registered under a name.
+  /// Returns `None` if no component is registered under it.
+  pub fn deserialize(
+    &self,
+    name: &str,
+    deserializer: &mut dyn erased_serde::Deserializer,
+  ) -> Option<Result<Box<dyn StoredComponent>, erased_serde::Error>> {
+    let registration = self.registrations.get(name)?;
+    Some((registration.deserialize)(deserializer))
+  }
+  /// Clone the registered components out of an entity, by name.
+  pub fn capture(
+    &self,
+    world: &World,
+    entity: Entity,
+  ) -> BTreeMap<String, Box<dyn StoredComponent>> {
+    self
+      .registrations
+      .iter()
+      .filter_map(|(name, registration)| {
+        let component = (registration.capture)(world, entity)?;
+        Some((name.clone(), component))
+      })
+      .collect()
+  }
+}
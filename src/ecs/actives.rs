@@ -15,4 +15,10 @@ impl Actives {
   pub fn set_camera(&mut self, entity: Entity) {
     self.camera.replace(entity);
   }
+  /// Forget an entity that was despawned, so it's no longer active.
+  pub fn forget(&mut self, entity: Entity) {
+    if self.camera == Some(entity) {
+      self.camera = None;
+    }
+  }
 }
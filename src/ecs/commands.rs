@@ -0,0 +1,85 @@
+use crate::{Command, CommandQueue, Component, Context, EngineError, Entity, Scenes, World};
+use hecs::DynamicBundle;
+
+/// A deferred change to a world.
+type WorldCommand = Box<dyn FnOnce(&mut World)>;
+
+/// Records changes to a world, such as spawning and despawning entities,
+/// to apply them later. This allows changes to be made while the world is
+/// borrowed by a query. The buffer in `Context` is applied at the end of
+/// every scene callback, so the entities it spawns are owned by the scene,
+/// and after the scene frame and postframe for changes recorded elsewhere.
+/// A buffer can also be enqueued as a command.
+#[derive(Default)]
+pub struct WorldCommands {
+  commands: Vec<WorldCommand>,
+}
+
+impl WorldCommands {
+  /// Create a new, empty world command buffer.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Record spawning an entity.
+  pub fn spawn(&mut self, components: impl DynamicBundle + 'static) {
+    self.push(move |world| {
+      world.spawn_entity(components);
+    });
+  }
+  /// Record despawning an entity. Entities that are already despawned when
+  /// the buffer is applied are skipped.
+  pub fn despawn(&mut self, entity: Entity) {
+    self.push(move |world| {
+      let _ = world.despawn(entity);
+    });
+  }
+  /// Record inserting a component into an entity.
+  pub fn insert_component<T: Component + 'static>(&mut self, entity: Entity, component: T) {
+    self.push(move |world| {
+      let _ = world.insert_component(entity, component);
+    });
+  }
+  /// Record inserting components into an entity.
+  pub fn insert_components(&mut self, entity: Entity, components: impl DynamicBundle + 'static) {
+    self.push(move |world| {
+      let _ = world.insert_components(entity, components);
+    });
+  }
+  /// Record removing a component from an entity.
+  pub fn remove_component<T: Component + 'static>(&mut self, entity: Entity) {
+    self.push(move |world| {
+      let _ = world.remove_component::<T>(entity);
+    });
+  }
+  /// Record a custom change.
+  pub fn push(&mut self, command: impl FnOnce(&mut World) + 'static) {
+    self.commands.push(Box::new(command));
+  }
+  /// Get the number of changes recorded.
+  pub fn len(&self) -> usize {
+    self.commands.len()
+  }
+  /// Get whether no changes are recorded.
+  pub fn is_empty(&self) -> bool {
+    self.commands.is_empty()
+  }
+  /// Apply the recorded changes to a world, in the order they were
+  /// recorded, emptying the buffer.
+  pub fn apply(&mut self, world: &mut World) {
+    for command in self.commands.drain(..) {
+      command(world);
+    }
+  }
+}
+
+impl Command for WorldCommands {
+  fn execute(
+    mut self: Box<Self>,
+    _command_queue: &mut CommandQueue,
+    _scenes: &mut Scenes,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    self.apply(&mut context.world);
+    Ok(())
+  }
+}
@@ -3,12 +3,24 @@ pub trait Component: Send + Sync {}
 
 /// Component variants.
 pub mod components {
-  use crate::{Color, Component, Matrix4, Mesh, Point, Scale, Size, Texture, Vector};
+  use crate::{Color, Component, Entity, Matrix4, Mesh, Point, Scale, Size, Texture, Vector};
+  use serde::{Deserialize, Serialize};
+  use std::any::TypeId;
 
-  /// The position and scale of an entity.
+  /// The position, scale and rotation of an entity.
+  /// The entity is rotated around its origin, which is in the unscaled
+  /// coordinates of its mesh. Both default to zero.
+  #[derive(Clone, Serialize, Deserialize)]
   pub struct Transform {
     pub position: Point,
     pub scale: Scale,
+    /// The rotation in radians. Since the y axis points down, positive
+    /// rotations are clockwise.
+    #[serde(default)]
+    pub rotation: f32,
+    /// The pivot of the rotation.
+    #[serde(default)]
+    pub origin: Point,
   }
 
   impl Transform {
@@ -17,33 +29,142 @@ pub mod components {
       Self {
         position: position.into(),
         scale: scale.into(),
+        rotation: 0.0,
+        origin: Point::new(0.0, 0.0),
       }
     }
+    /// Set the rotation, in radians.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+      self.rotation = rotation;
+      self
+    }
+    /// Set the origin, which the entity is rotated around.
+    pub fn with_origin(mut self, origin: impl Into<Point>) -> Self {
+      self.origin = origin.into();
+      self
+    }
   }
 
   impl Component for Transform {}
 
   /// Allows an entity to be rendered.
+  #[derive(Clone, Serialize, Deserialize)]
   pub struct Renderable {
     pub color: Color,
     pub texture: Texture,
     pub mesh: Mesh,
+    /// The layer to render in. Lower layers are rendered first, so higher
+    /// layers are drawn over them. Defaults to zero.
+    #[serde(default)]
+    pub layer: i32,
   }
 
   impl Renderable {
-    /// Create a new renderable component.
+    /// Create a new renderable component in layer zero.
     pub fn new(color: Color, texture: Texture, mesh: Mesh) -> Self {
       Self {
         color: color,
         texture: texture,
         mesh: mesh,
+        layer: 0,
       }
     }
+    /// Set the layer to render in.
+    pub fn with_layer(mut self, layer: i32) -> Self {
+      self.layer = layer;
+      self
+    }
   }
 
   impl Component for Renderable {}
 
+  /// Allows an entity to be rendered as text, with a font added with
+  /// `Renderer::add_font`. Text with a font that wasn't added is skipped.
+  /// The text's top-left corner is at the entity's position, and its scale
+  /// is the size of a pixel of the font.
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct Text {
+    pub font: String,
+    /// The sections of the text, which are drawn one after another, each in
+    /// its own color.
+    pub sections: Vec<TextSection>,
+    /// How the lines are aligned. Defaults to the left.
+    #[serde(default)]
+    pub align: TextAlign,
+    /// The width to wrap lines to, in pixels of the font. Lines aren't
+    /// wrapped if it's unset.
+    #[serde(default)]
+    pub max_width: Option<f32>,
+    /// The layer to render in. Defaults to zero.
+    #[serde(default)]
+    pub layer: i32,
+  }
+
+  impl Text {
+    /// Create a new text component with a single section, aligned to the
+    /// left in layer zero.
+    pub fn new(font: impl ToString, text: impl ToString, color: Color) -> Self {
+      Self {
+        font: font.to_string(),
+        sections: vec![TextSection::new(text, color)],
+        align: TextAlign::default(),
+        max_width: None,
+        layer: 0,
+      }
+    }
+    /// Add a section after the others.
+    pub fn with_section(mut self, text: impl ToString, color: Color) -> Self {
+      self.sections.push(TextSection::new(text, color));
+      self
+    }
+    /// Set how the lines are aligned.
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+      self.align = align;
+      self
+    }
+    /// Set the width to wrap lines to, in pixels of the font.
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+      self.max_width.replace(max_width);
+      self
+    }
+    /// Set the layer to render in.
+    pub fn with_layer(mut self, layer: i32) -> Self {
+      self.layer = layer;
+      self
+    }
+  }
+
+  impl Component for Text {}
+
+  /// A section of text in a single color.
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct TextSection {
+    pub text: String,
+    pub color: Color,
+  }
+
+  impl TextSection {
+    /// Create a new text section.
+    pub fn new(text: impl ToString, color: Color) -> Self {
+      Self {
+        text: text.to_string(),
+        color: color,
+      }
+    }
+  }
+
+  /// How the lines of text are aligned, within its maximum width or its
+  /// widest line.
+  #[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+  pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+  }
+
   /// The camera component.
+  #[derive(Clone, Serialize, Deserialize)]
   pub struct Camera {
     pub offset: [f32; 2],
   }
@@ -63,11 +184,22 @@ pub mod components {
       let top = position.y - (fbd.1 / 2) as f32 + self.offset[1];
       Matrix4::new_orthographic(left, right, bottom, top, -1.0, 1.0).into()
     }
+    /// Convert a point on the screen to world space.
+    /// `fbd` is the frame buffer dimensions.
+    /// `position` is the position of the entity that holds the camera.
+    /// `screen` is the point on the screen, in physical pixels relative to
+    /// the top-left corner of the window.
+    pub fn screen_to_world(&self, fbd: (u32, u32), position: Point, screen: Point) -> Point {
+      let left = position.x - (fbd.0 / 2) as f32 + self.offset[0];
+      let top = position.y - (fbd.1 / 2) as f32 + self.offset[1];
+      Point::new(left + screen.x, top + screen.y)
+    }
   }
 
   impl Component for Camera {}
 
   /// The rigid body component.
+  #[derive(Clone, Serialize, Deserialize)]
   pub struct RigidBody {
     /// The velocity of the rigid body.
     pub velocity: Vector,
@@ -88,6 +220,7 @@ pub mod components {
   impl Component for RigidBody {}
 
   /// The collider component.
+  #[derive(Clone, Serialize, Deserialize)]
   pub struct Collider {
     pub offset: Point,
     pub size: Size,
@@ -101,7 +234,138 @@ pub mod components {
         size: size.into(),
       }
     }
+    /// Get the position and size of the collider in the world. Colliders are
+    /// axis-aligned, so a rotated entity collides as the bounding box of its
+    /// rotated collider.
+    pub fn bounds(&self, transform: &GlobalTransform) -> (Point, Size) {
+      // Transform the corners of the collider.
+      let corners = [
+        Point::new(0.0, 0.0),
+        Point::new(self.size.w, 0.0),
+        Point::new(self.size.w, self.size.h),
+        Point::new(0.0, self.size.h),
+      ]
+      .map(|corner| transform.transform_offset(self.offset + corner));
+      // Get the bounding box of the corners.
+      let min = corners
+        .into_iter()
+        .reduce(|a, b| Point::new(a.x.min(b.x), a.y.min(b.y)))
+        .unwrap_or_default();
+      let max = corners
+        .into_iter()
+        .reduce(|a, b| Point::new(a.x.max(b.x), a.y.max(b.y)))
+        .unwrap_or_default();
+      (min, Size::new(max.x - min.x, max.y - min.y))
+    }
   }
 
   impl Component for Collider {}
+
+  /// The position, scale and rotation of an entity in the world, computed
+  /// from its `Transform`, which is relative to its parent if it has one.
+  /// Children are rotated along with their parent, around the parent's
+  /// origin. Scale isn't inherited, since it's the size of the entity's mesh.
+  /// Propagated automatically before the simulator and renderer run.
+  #[derive(Clone, Copy)]
+  pub struct GlobalTransform {
+    pub position: Point,
+    pub scale: Scale,
+    pub rotation: f32,
+    pub origin: Point,
+  }
+
+  impl GlobalTransform {
+    /// Get the point in the world that the entity is rotated around.
+    pub fn pivot(&self) -> Point {
+      self.position + self.origin * self.scale
+    }
+    /// Transform a vertex of the entity's mesh into the world.
+    pub fn transform_vertex(&self, vertex: Point) -> Point {
+      self.pivot() + ((vertex - self.origin) * self.scale).rotate(self.rotation)
+    }
+    /// Transform an unscaled offset from the entity's position, such as a
+    /// collider's or a child's, into the world.
+    pub fn transform_offset(&self, offset: Point) -> Point {
+      let pivot = self.pivot();
+      pivot + (self.position + offset - pivot).rotate(self.rotation)
+    }
+  }
+
+  impl Default for GlobalTransform {
+    fn default() -> Self {
+      Self {
+        position: Point::new(0.0, 0.0),
+        scale: Scale::identity(),
+        rotation: 0.0,
+        origin: Point::new(0.0, 0.0),
+      }
+    }
+  }
+
+  impl From<&Transform> for GlobalTransform {
+    fn from(transform: &Transform) -> Self {
+      Self {
+        position: transform.position,
+        scale: transform.scale,
+        rotation: transform.rotation,
+        origin: transform.origin,
+      }
+    }
+  }
+
+  impl Component for GlobalTransform {}
+
+  /// The parent of an entity, which its `Transform` is relative to. The
+  /// parent's `Children` are kept in sync by the `World`.
+  #[derive(Clone, Copy)]
+  pub struct Parent {
+    pub entity: Entity,
+  }
+
+  impl Parent {
+    /// Create a new parent component.
+    pub fn new(entity: Entity) -> Self {
+      Self { entity: entity }
+    }
+  }
+
+  impl Component for Parent {}
+
+  /// The children of an entity. Despawning an entity despawns its children.
+  /// Maintained by the `World` from the children's `Parent` components.
+  #[derive(Clone, Default)]
+  pub struct Children {
+    pub(crate) entities: Vec<Entity>,
+  }
+
+  impl Children {
+    /// Get the children.
+    pub fn entities(&self) -> &[Entity] {
+      &self.entities
+    }
+  }
+
+  impl Component for Children {}
+
+  /// The scene that spawned an entity. Owned entities are despawned when
+  /// their scene is unloaded, unless they are `Persistent`.
+  /// Added automatically to entities spawned from scene callbacks.
+  pub struct Owner {
+    pub scene: TypeId,
+  }
+
+  impl Owner {
+    /// Create a new owner component.
+    pub fn new(scene: TypeId) -> Self {
+      Self { scene: scene }
+    }
+  }
+
+  impl Component for Owner {}
+
+  /// Marks an entity to carry over when the scene that owns it is unloaded.
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct Persistent;
+
+  impl Component for Persistent {}
 }
@@ -7,4 +7,8 @@ pub enum EcsError {
   EntityNotFound,
   #[error("Unsatisfied inspect")]
   UnsatisfiedInspect,
+  #[error("Component was not found")]
+  ComponentNotFound,
+  #[error("An entity can't be parented to itself or its descendants")]
+  HierarchyCycle,
 }
@@ -1,4 +1,5 @@
 pub mod actives;
+pub mod commands;
 pub mod component;
 pub mod entity;
 pub mod error;
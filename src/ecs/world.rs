@@ -1,11 +1,19 @@
-use crate::{Actives, EcsError, Entity};
-use hecs::{DynamicBundle, Query, QueryBorrow, QueryMut, QueryOne, QueryOneError};
+use crate::{
+  Actives, Children, Component, EcsError, Entity, GlobalTransform, Owner, Parent, Persistent,
+  Transform,
+};
+use hecs::{
+  ComponentError, DynamicBundle, Query, QueryBorrow, QueryMut, QueryOne, QueryOneError, Without,
+};
+use std::any::TypeId;
 
 /// Manages entities and their components.
 #[derive(Default)]
 pub struct World {
   inner: hecs::World,
   pub actives: Actives,
+  /// The scene that owns the entities spawned, if any.
+  owner: Option<TypeId>,
 }
 
 impl World {
@@ -14,8 +22,308 @@ impl World {
     Self::default()
   }
   /// Spawn an entity.
+  /// If a scene owner is set, the entity is owned by that scene.
   pub fn spawn_entity(&mut self, components: impl DynamicBundle) -> Entity {
-    self.inner.spawn(components)
+    let entity = self.inner.spawn(components);
+    if let Some(scene) = self.owner {
+      // The entity was just spawned, so inserting can't fail.
+      let _ = self.inner.insert_one(entity, Owner::new(scene));
+    }
+    self.attach(entity);
+    entity
+  }
+  /// Set the scene that owns the entities spawned from now on, if any.
+  pub fn set_owner(&mut self, owner: Option<TypeId>) {
+    self.owner = owner;
+  }
+  /// Despawn the entities owned by a scene, except for persistent ones.
+  /// Persistent descendants of the despawned entities become root
+  /// entities, where they were in the world.
+  pub fn despawn_owned(&mut self, scene: TypeId) {
+    let owned = self
+      .inner
+      .query_mut::<Without<&Owner, &Persistent>>()
+      .into_iter()
+      .filter(|(_, owner)| owner.scene == scene)
+      .map(|(entity, _)| entity)
+      .collect::<Vec<_>>();
+    for entity in owned {
+      let _ = self.despawn_tree(entity, true);
+    }
+  }
+  /// Despawn an entity and its descendants.
+  pub fn despawn(&mut self, entity: Entity) -> Result<(), EcsError> {
+    self.despawn_tree(entity, false)
+  }
+  /// Despawn an entity and its descendants, detaching it from its parent.
+  /// If `keep_persistent` is set, persistent descendants are kept.
+  fn despawn_tree(&mut self, entity: Entity, keep_persistent: bool) -> Result<(), EcsError> {
+    if !self.contains(entity) {
+      Err(EcsError::EntityNotFound)?;
+    }
+    if let Some(parent) = self.parent(entity) {
+      self.detach(entity, parent);
+    }
+    self.despawn_subtree(entity, keep_persistent);
+    Ok(())
+  }
+  /// Despawn an entity and its descendants, without detaching it from its
+  /// parent. If `keep_persistent` is set, persistent descendants are made
+  /// root entities instead.
+  fn despawn_subtree(&mut self, entity: Entity, keep_persistent: bool) {
+    let children = self.children(entity);
+    if self.inner.despawn(entity).is_ok() {
+      self.forget(entity);
+    }
+    for child in children {
+      // Skip children that are no longer parented to the entity.
+      if self.parent(child) != Some(entity) {
+        continue;
+      }
+      if keep_persistent && self.inner.get::<&Persistent>(child).is_ok() {
+        self.make_root(child);
+      } else {
+        self.despawn_subtree(child, keep_persistent);
+      }
+    }
+  }
+  /// Make an entity whose parent was despawned a root entity, keeping it
+  /// where it was in the world when the transforms were last propagated.
+  fn make_root(&mut self, entity: Entity) {
+    let _ = self.inner.remove_one::<Parent>(entity);
+    let query = self
+      .inner
+      .query_one_mut::<(&mut Transform, &GlobalTransform)>(entity);
+    if let Ok((transform, global_transform)) = query {
+      transform.position = global_transform.position;
+      transform.rotation = global_transform.rotation;
+    }
+  }
+  /// Despawn entities, skipping those that don't exist.
+  /// Returns the number of entities despawned.
+  pub fn despawn_batch(&mut self, entities: impl IntoIterator<Item = Entity>) -> usize {
+    entities
+      .into_iter()
+      .filter(|entity| self.despawn(*entity).is_ok())
+      .count()
+  }
+  /// Despawn every entity.
+  pub fn clear(&mut self) {
+    let entities = self.entities().collect::<Vec<_>>();
+    self.inner.clear();
+    for entity in entities {
+      self.forget(entity);
+    }
+  }
+  /// Clean up the engine state that references a despawned entity. The
+  /// simulator only keeps entity colliders for the duration of a step, so
+  /// it holds none to clean up.
+  fn forget(&mut self, entity: Entity) {
+    self.actives.forget(entity);
+  }
+  /// Insert a component into an entity, replacing the component of the
+  /// same type.
+  pub fn insert_component<T: Component + 'static>(
+    &mut self,
+    entity: Entity,
+    component: T,
+  ) -> Result<(), EcsError> {
+    self.insert_components(entity, (component,))
+  }
+  /// Insert components into an entity, replacing the components of the
+  /// same types. If they include a `Parent` that is the entity or one of
+  /// its descendants, the entity keeps its parent and
+  /// `EcsError::HierarchyCycle` is returned, though the other components
+  /// are still inserted.
+  pub fn insert_components(
+    &mut self,
+    entity: Entity,
+    components: impl DynamicBundle,
+  ) -> Result<(), EcsError> {
+    let old_parent = self.parent(entity);
+    self
+      .inner
+      .insert(entity, components)
+      .map_err(|_| EcsError::EntityNotFound)?;
+    // Restore the old parent if the new one would make a cycle.
+    if let Some(parent) = self.parent(entity) {
+      if Some(parent) != old_parent && self.is_descendant(parent, entity) {
+        match old_parent {
+          Some(old_parent) => {
+            let _ = self.inner.insert_one(entity, Parent::new(old_parent));
+          },
+          None => {
+            let _ = self.inner.remove_one::<Parent>(entity);
+          },
+        }
+        Err(EcsError::HierarchyCycle)?;
+      }
+    }
+    self.reparent(entity, old_parent);
+    Ok(())
+  }
+  /// Remove a component from an entity, returning it.
+  pub fn remove_component<T: Component + 'static>(
+    &mut self,
+    entity: Entity,
+  ) -> Result<T, EcsError> {
+    let old_parent = self.parent(entity);
+    let component = self
+      .inner
+      .remove_one::<T>(entity)
+      .map_err(|error| match error {
+        ComponentError::NoSuchEntity => EcsError::EntityNotFound,
+        ComponentError::MissingComponent(_) => EcsError::ComponentNotFound,
+      })?;
+    self.reparent(entity, old_parent);
+    Ok(component)
+  }
+  /// Parent an entity to another, making its transform relative to the
+  /// parent's.
+  pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), EcsError> {
+    if !self.contains(child) || !self.contains(parent) {
+      Err(EcsError::EntityNotFound)?;
+    }
+    // The parent can't be the child or one of its descendants.
+    if self.is_descendant(parent, child) {
+      Err(EcsError::HierarchyCycle)?;
+    }
+    self.insert_component(child, Parent::new(parent))
+  }
+  /// Get whether an entity is another entity or one of its descendants.
+  fn is_descendant(&self, entity: Entity, ancestor: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+      if entity == ancestor {
+        return true;
+      }
+      current = self.parent(entity);
+    }
+    false
+  }
+  /// Unparent an entity, making its transform absolute.
+  pub fn remove_parent(&mut self, child: Entity) -> Result<(), EcsError> {
+    self.remove_component::<Parent>(child)?;
+    Ok(())
+  }
+  /// Get the parent of an entity, if it has one.
+  pub fn parent(&self, entity: Entity) -> Option<Entity> {
+    let parent = self.inner.get::<&Parent>(entity).ok()?;
+    Some(parent.entity)
+  }
+  /// Get the children of an entity.
+  pub fn children(&self, entity: Entity) -> Vec<Entity> {
+    self
+      .inner
+      .get::<&Children>(entity)
+      .map_or_else(|_| Vec::new(), |children| children.entities.clone())
+  }
+  /// Keep the children of an entity's old and new parents in sync, after
+  /// its components changed.
+  fn reparent(&mut self, entity: Entity, old_parent: Option<Entity>) {
+    if self.parent(entity) == old_parent {
+      return;
+    }
+    if let Some(old_parent) = old_parent {
+      self.detach(entity, old_parent);
+    }
+    self.attach(entity);
+  }
+  /// Add an entity to the children of its parent, if it has one.
+  fn attach(&mut self, entity: Entity) {
+    let Some(parent) = self.parent(entity) else {
+      return;
+    };
+    if let Ok(children) = self.inner.query_one_mut::<&mut Children>(parent) {
+      if !children.entities.contains(&entity) {
+        children.entities.push(entity);
+      }
+    } else {
+      let children = Children {
+        entities: vec![entity],
+      };
+      let _ = self.inner.insert_one(parent, children);
+    }
+  }
+  /// Remove an entity from the children of a parent.
+  fn detach(&mut self, entity: Entity, parent: Entity) {
+    if let Ok(children) = self.inner.query_one_mut::<&mut Children>(parent) {
+      children.entities.retain(|child| *child != entity);
+    }
+  }
+  /// Compute the global transforms of the entities from their transforms,
+  /// which are relative to their parents. Invoked before the simulator and
+  /// renderer run.
+  pub fn propagate_transforms(&mut self) {
+    // Add a global transform to the entities that lack one.
+    let missing = self
+      .inner
+      .query_mut::<Without<&Transform, &GlobalTransform>>()
+      .into_iter()
+      .map(|(entity, transform)| (entity, GlobalTransform::from(transform)))
+      .collect::<Vec<_>>();
+    for (entity, global_transform) in missing {
+      let _ = self.inner.insert_one(entity, global_transform);
+    }
+    // The global transforms of the root entities are their transforms.
+    let query = self
+      .inner
+      .query_mut::<Without<(&Transform, &mut GlobalTransform), &Parent>>();
+    for (_, (transform, global_transform)) in query {
+      *global_transform = transform.into();
+    }
+    // Propagate the global transforms from the roots down.
+    let mut stack = self
+      .inner
+      .query_mut::<Without<(Option<&GlobalTransform>, &Children), &Parent>>()
+      .into_iter()
+      .map(|(entity, (global_transform, _))| {
+        (entity, global_transform.copied().unwrap_or_default())
+      })
+      .collect::<Vec<_>>();
+    while let Some((parent, parent_transform)) = stack.pop() {
+      for child in self.children(parent) {
+        let query = self
+          .inner
+          .query_one_mut::<(&Parent, Option<&Transform>, Option<&mut GlobalTransform>)>(child);
+        let Ok((child_parent, transform, global_transform)) = query else {
+          continue;
+        };
+        // Skip children that are no longer parented to the entity.
+        if child_parent.entity != parent {
+          continue;
+        }
+        // Children are positioned and rotated relative to the parent.
+        // Children without a transform pass the parent's transform on.
+        let mut child_transform = parent_transform;
+        if let (Some(transform), Some(global_transform)) = (transform, global_transform) {
+          *global_transform = GlobalTransform {
+            position: parent_transform.transform_offset(transform.position),
+            scale: transform.scale,
+            rotation: parent_transform.rotation + transform.rotation,
+            origin: transform.origin,
+          };
+          child_transform = *global_transform;
+        }
+        stack.push((child, child_transform));
+      }
+    }
+  }
+  /// Get whether an entity exists.
+  pub fn contains(&self, entity: Entity) -> bool {
+    self.inner.contains(entity)
+  }
+  /// Get the number of entities.
+  pub fn len(&self) -> u32 {
+    self.inner.len()
+  }
+  /// Get whether there are no entities.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+  /// Get the entities in the world.
+  pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+    self.inner.iter().map(|entity| entity.entity())
   }
   /// Query dynamically.
   #[inline]
@@ -49,3 +357,27 @@ impl World {
       })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn inserted_parents_cannot_form_cycles() {
+    let mut world = World::new();
+    let root = world.spawn_entity(());
+    let child = world.spawn_entity((Parent::new(root),));
+    let grandchild = world.spawn_entity((Parent::new(child),));
+    let result = world.insert_components(root, (Parent::new(grandchild),));
+    assert!(matches!(result, Err(EcsError::HierarchyCycle)));
+    assert_eq!(world.parent(root), None);
+    let result = world.insert_components(child, (Parent::new(grandchild),));
+    assert!(matches!(result, Err(EcsError::HierarchyCycle)));
+    assert_eq!(world.parent(child), Some(root));
+    let result = world.insert_component(child, Parent::new(child));
+    assert!(matches!(result, Err(EcsError::HierarchyCycle)));
+    assert_eq!(world.parent(child), Some(root));
+    assert_eq!(world.children(root), vec![child]);
+    assert!(world.children(grandchild).is_empty());
+  }
+}
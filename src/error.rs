@@ -1,4 +1,6 @@
-use crate::{AppError, EcsError, GfxError, SceneError};
+use crate::{
+  AppError, DataError, EcsError, GfxError, InputError, PreloadError, SceneError, TimerError,
+};
 use thiserror::Error;
 
 /// Engine errors.
@@ -12,4 +14,12 @@ pub enum EngineError {
   App(#[from] AppError),
   #[error("{0}")]
   Scene(#[from] SceneError),
+  #[error("{0}")]
+  Input(#[from] InputError),
+  #[error("{0}")]
+  Preload(#[from] PreloadError),
+  #[error("{0}")]
+  Data(#[from] DataError),
+  #[error("{0}")]
+  Timer(#[from] TimerError),
 }
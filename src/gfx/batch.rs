@@ -0,0 +1,207 @@
+use crate::{
+  Display, GfxError, GlobalTransform, Programs, Renderable, Textures, Transform, Vertex,
+};
+use glium::{
+  index::PrimitiveType, uniform, Blend, DrawParameters, Frame, IndexBuffer, Surface, VertexBuffer,
+};
+use std::collections::BTreeMap;
+
+/// The meshes of a static batch that share a layer and sampler, drawn with
+/// a single draw call.
+struct StaticBatchPart {
+  vertex_buffer: VertexBuffer<Vertex>,
+  index_buffer: IndexBuffer<u32>,
+  sampler_id: u16,
+}
+
+/// Static meshes, such as level geometry, whose vertices are written to
+/// immutable buffers once. Drawing a static batch only changes the
+/// projection.
+pub struct StaticBatch {
+  /// The parts of the batch, by layer.
+  parts: BTreeMap<i32, Vec<StaticBatchPart>>,
+}
+
+impl StaticBatch {
+  /// Create a new static batch from transformed renderables. A headless
+  /// static batch has no display, so it has nothing to draw.
+  pub fn new(
+    display: Option<&Display>,
+    textures: &Textures,
+    renderables: impl IntoIterator<Item = (Transform, Renderable)>,
+  ) -> Result<Self, GfxError> {
+    let data = batch_data(textures, renderables)?;
+    // Upload the data to immutable buffers, if there is a display.
+    let mut parts = BTreeMap::<i32, Vec<StaticBatchPart>>::new();
+    if let Some(display) = display {
+      for ((layer, sampler_id), (vertices, indices)) in data {
+        let part = StaticBatchPart {
+          vertex_buffer: VertexBuffer::immutable(display, &vertices)?,
+          index_buffer: IndexBuffer::immutable(display, PrimitiveType::TrianglesList, &indices)?,
+          sampler_id: sampler_id,
+        };
+        parts.entry(layer).or_default().push(part);
+      }
+    }
+    Ok(Self { parts: parts })
+  }
+  /// Get the layers the batch has meshes in.
+  pub fn layers(&self) -> impl Iterator<Item = i32> + '_ {
+    self.parts.keys().copied()
+  }
+  /// Draw the meshes of the batch in a layer.
+  pub fn draw(
+    &self,
+    frame: &mut Frame,
+    programs: &Programs,
+    textures: &Textures,
+    projection: [[f32; 4]; 4],
+    layer: i32,
+  ) -> Result<(), GfxError> {
+    let Some(parts) = self.parts.get(&layer) else {
+      return Ok(());
+    };
+    for part in parts {
+      // Get the sampler.
+      let sampler = textures.get_sampler(part.sampler_id)?;
+      // Draw the frame.
+      frame.draw(
+        &part.vertex_buffer,
+        &part.index_buffer,
+        &programs.basic,
+        &uniform! {
+          u_projection: projection,
+          u_sampler: sampler,
+        },
+        &DrawParameters {
+          blend: Blend::alpha_blending(),
+          ..Default::default()
+        },
+      )?;
+    }
+    Ok(())
+  }
+}
+
+/// Meshes that change every frame, such as debug shapes, whose vertices are
+/// written to dynamic buffers that are reused across frames. The buffers
+/// are only reallocated when the meshes outgrow them.
+#[derive(Default)]
+pub struct DynamicBatch {
+  buffers: Option<(VertexBuffer<Vertex>, IndexBuffer<u32>)>,
+}
+
+impl DynamicBatch {
+  /// Create a new dynamic batch. Its buffers are allocated when it's first
+  /// drawn.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Draw transformed renderables, by layer, with a draw call per layer and
+  /// sampler.
+  pub fn draw(
+    &mut self,
+    display: &Display,
+    frame: &mut Frame,
+    programs: &Programs,
+    textures: &Textures,
+    projection: [[f32; 4]; 4],
+    renderables: impl IntoIterator<Item = (Transform, Renderable)>,
+  ) -> Result<(), GfxError> {
+    // Concatenate the vertices and indices of the renderables, keeping
+    // track of the range of indices of each sampler.
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut ranges = Vec::new();
+    for ((_, sampler_id), (part_vertices, part_indices)) in batch_data(textures, renderables)? {
+      let base = vertices.len() as u32;
+      let start = indices.len();
+      vertices.extend(part_vertices);
+      indices.extend(part_indices.into_iter().map(|index| base + index));
+      ranges.push((start..indices.len(), sampler_id));
+    }
+    if indices.is_empty() {
+      return Ok(());
+    }
+    // Grow the buffers if they're too small.
+    let fits = self
+      .buffers
+      .as_ref()
+      .is_some_and(|(vertex_buffer, index_buffer)| {
+        vertex_buffer.len() >= vertices.len() && index_buffer.len() >= indices.len()
+      });
+    if !fits {
+      self.buffers.replace((
+        VertexBuffer::empty_dynamic(display, vertices.len().next_power_of_two())?,
+        IndexBuffer::empty_dynamic(
+          display,
+          PrimitiveType::TrianglesList,
+          indices.len().next_power_of_two(),
+        )?,
+      ));
+    }
+    let Some((vertex_buffer, index_buffer)) = &self.buffers else {
+      return Ok(());
+    };
+    // Write the data to the buffers.
+    vertex_buffer
+      .slice(0..vertices.len())
+      .ok_or(GfxError::BufferSlice)?
+      .write(&vertices);
+    index_buffer
+      .slice(0..indices.len())
+      .ok_or(GfxError::BufferSlice)?
+      .write(&indices);
+    for (range, sampler_id) in ranges {
+      // Get the sampler.
+      let sampler = textures.get_sampler(sampler_id)?;
+      // Draw the frame.
+      frame.draw(
+        vertex_buffer,
+        index_buffer.slice(range).ok_or(GfxError::BufferSlice)?,
+        &programs.basic,
+        &uniform! {
+          u_projection: projection,
+          u_sampler: sampler,
+        },
+        &DrawParameters {
+          blend: Blend::alpha_blending(),
+          ..Default::default()
+        },
+      )?;
+    }
+    Ok(())
+  }
+}
+
+/// The vertices and indices of a batch, by layer and sampler.
+type BatchData = BTreeMap<(i32, u16), (Vec<Vertex>, Vec<u32>)>;
+
+/// Write the vertices and indices of transformed renderables, grouped by
+/// layer and sampler.
+fn batch_data(
+  textures: &Textures,
+  renderables: impl IntoIterator<Item = (Transform, Renderable)>,
+) -> Result<BatchData, GfxError> {
+  let mut data = BatchData::new();
+  for (transform, mut renderable) in renderables {
+    let texture_info = textures.get_texture_info(renderable.texture.get())?;
+    let (vertices, indices) = data
+      .entry((renderable.layer, texture_info.sampler_id))
+      .or_default();
+    // Offset the indices of the mesh by the vertices already written.
+    let base = vertices.len() as u32;
+    indices.extend(renderable.mesh.indices().iter().map(|index| base + index));
+    // Transform the vertices of the mesh.
+    let transform = GlobalTransform::from(&transform);
+    let color = renderable.color.into();
+    for (i, &vertex) in renderable.mesh.vertices().iter().enumerate() {
+      vertices.push(Vertex {
+        position: transform.transform_vertex(vertex).into(),
+        color: color,
+        texture_coord: texture_info.texture_coord(&renderable.mesh, i),
+      });
+    }
+  }
+  Ok(data)
+}
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// A color. Defaults to white.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Color {
   red: f32,
   green: f32,
@@ -75,6 +77,13 @@ impl Color {
       ..Default::default()
     }
   }
+  /// Get the color with its alpha replaced.
+  pub fn with_alpha(self, alpha: f32) -> Self {
+    Self {
+      alpha: alpha,
+      ..self
+    }
+  }
 }
 
 impl From<Color> for [f32; 4] {
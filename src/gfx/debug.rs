@@ -0,0 +1,247 @@
+use crate::{Color, LineJoin, Mesh, Point, Renderable, Size, Texture, Transform, Vector};
+
+/// The number of segments of a debug circle.
+const CIRCLE_SEGMENTS: u32 = 24;
+
+/// The width and height of a glyph of the debug font, in pixels.
+const GLYPH_SIZE: (usize, usize) = (3, 5);
+
+/// Immediate-mode debug drawing. Shapes are drawn on top of everything,
+/// then cleared at the end of the frame. While disabled, nothing is
+/// recorded.
+pub struct DebugDraw {
+  /// Whether shapes are recorded and drawn.
+  enabled: bool,
+  /// The thickness of lines and outlines.
+  thickness: f32,
+  /// The size of a pixel of the debug font.
+  text_scale: f32,
+  /// The shapes recorded this frame.
+  shapes: Vec<(Transform, Renderable)>,
+}
+
+impl Default for DebugDraw {
+  fn default() -> Self {
+    Self {
+      enabled: cfg!(feature = "show_hitboxes"),
+      thickness: 1.0,
+      text_scale: 2.0,
+      shapes: Vec::new(),
+    }
+  }
+}
+
+impl DebugDraw {
+  /// Create a new debug drawer. It's enabled by default if the
+  /// `show_hitboxes` feature is.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Get whether debug drawing is enabled.
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+  /// Enable or disable debug drawing. Disabling it discards the shapes
+  /// recorded this frame.
+  pub fn set_enabled(&mut self, enabled: bool) {
+    self.enabled = enabled;
+    if !enabled {
+      self.shapes.clear();
+    }
+  }
+  /// Toggle debug drawing.
+  pub fn toggle(&mut self) {
+    self.set_enabled(!self.enabled);
+  }
+  /// Set the thickness of lines and outlines. Defaults to 1.
+  pub fn set_thickness(&mut self, thickness: f32) {
+    self.thickness = thickness;
+  }
+  /// Set the size of a pixel of the debug font. Defaults to 2.
+  pub fn set_text_scale(&mut self, text_scale: f32) {
+    self.text_scale = text_scale;
+  }
+  /// Draw a line.
+  pub fn line(&mut self, start: impl Into<Point>, end: impl Into<Point>, color: Color) {
+    if self.enabled {
+      self.mesh(Mesh::line(start, end, self.thickness), color);
+    }
+  }
+  /// Draw the outline of a rect, whose position is its top-left corner.
+  pub fn rect(&mut self, position: impl Into<Point>, size: impl Into<Size>, color: Color) {
+    if !self.enabled {
+      return;
+    }
+    let (position, size) = (position.into(), size.into());
+    let corners = [
+      position,
+      Point::new(position.x + size.w, position.y),
+      position + size,
+      Point::new(position.x, position.y + size.h),
+      position,
+    ];
+    self.mesh(
+      Mesh::polyline(&corners, self.thickness, LineJoin::Miter),
+      color,
+    );
+  }
+  /// Draw the outline of a circle.
+  pub fn circle(&mut self, center: impl Into<Point>, radius: f32, color: Color) {
+    if !self.enabled {
+      return;
+    }
+    let center = center.into();
+    let mesh = Mesh::arc(
+      radius,
+      self.thickness,
+      0.0,
+      2.0 * std::f32::consts::PI,
+      CIRCLE_SEGMENTS,
+    );
+    self.shape(center, mesh, color);
+  }
+  /// Draw an arrow from `start` to `end`, such as a velocity or a contact
+  /// normal.
+  pub fn arrow(&mut self, start: impl Into<Point>, end: impl Into<Point>, color: Color) {
+    if !self.enabled {
+      return;
+    }
+    let (start, end) = (start.into(), end.into());
+    let direction = Vector::new(end.x - start.x, end.y - start.y);
+    let length = direction.norm();
+    if length == 0.0 {
+      return;
+    }
+    // The head is a third of the arrow, up to 4 lines thick.
+    let head_length = (length / 3.0).min(self.thickness * 4.0);
+    let back = direction / length * head_length;
+    let side = Vector::new(-back.y, back.x) / 2.0;
+    let head = [
+      Point::new(end.x - back.x + side.x, end.y - back.y + side.y),
+      end,
+      Point::new(end.x - back.x - side.x, end.y - back.y - side.y),
+    ];
+    self.mesh(Mesh::line(start, end, self.thickness), color);
+    self.mesh(
+      Mesh::polyline(&head, self.thickness, LineJoin::Miter),
+      color,
+    );
+  }
+  /// Draw text with the debug font, whose position is its top-left corner.
+  /// Letters are drawn as uppercase, and characters the font doesn't have
+  /// are drawn as `?`.
+  pub fn text(&mut self, position: impl Into<Point>, text: &str, color: Color) {
+    if !self.enabled {
+      return;
+    }
+    // Add a quad per pixel of each glyph.
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let (mut column, mut row) = (0, 0);
+    for character in text.chars() {
+      if character == '\n' {
+        column = 0;
+        row += 1;
+        continue;
+      }
+      let glyph = glyph(character);
+      for (y, bits) in glyph.iter().enumerate() {
+        for x in 0..GLYPH_SIZE.0 {
+          if bits & (0b100 >> x) == 0 {
+            continue;
+          }
+          let left = (column * (GLYPH_SIZE.0 + 1) + x) as f32;
+          let top = (row * (GLYPH_SIZE.1 + 1) + y) as f32;
+          let base = vertices.len() as u32;
+          vertices.extend([
+            Point::new(left, top),
+            Point::new(left + 1.0, top),
+            Point::new(left + 1.0, top + 1.0),
+            Point::new(left, top + 1.0),
+          ]);
+          indices.extend([base, base + 2, base + 1, base, base + 3, base + 2]);
+        }
+      }
+      column += 1;
+    }
+    if vertices.is_empty() {
+      return;
+    }
+    let transform = Transform::new(position, [self.text_scale, self.text_scale]);
+    self.shapes.push((
+      transform,
+      Renderable::new(color, Texture::none(), Mesh::new(vertices, indices)),
+    ));
+  }
+  /// Take the shapes recorded this frame.
+  pub fn take_shapes(&mut self) -> Vec<(Transform, Renderable)> {
+    std::mem::take(&mut self.shapes)
+  }
+  /// Record a mesh whose vertices are in world space.
+  fn mesh(&mut self, mesh: Mesh, color: Color) {
+    self.shape(Point::new(0.0, 0.0), mesh, color);
+  }
+  /// Record a mesh at a position.
+  fn shape(&mut self, position: Point, mesh: Mesh, color: Color) {
+    let transform = Transform::new(position, [1.0, 1.0]);
+    let renderable = Renderable::new(color, Texture::none(), mesh);
+    self.shapes.push((transform, renderable));
+  }
+}
+
+/// Get the rows of a glyph of the debug font, from top to bottom. The
+/// leftmost pixel of a row is its third bit.
+fn glyph(character: char) -> [u8; 5] {
+  match character.to_ascii_uppercase() {
+    ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+    '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+    '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+    '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+    '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+    '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+    '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+    '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+    '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+    '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+    '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+    'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+    'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+    'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+    'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+    'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+    'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+    'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+    'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+    'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+    'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+    'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+    'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+    'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+    'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+    'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+    'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+    'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+    'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+    'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+    'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+    'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+    'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+    'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+    'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+    'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+    'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+    '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+    ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+    ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+    '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+    '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+    '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+    '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+    '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+    '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+    ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+    '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+    '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+    _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+  }
+}
@@ -26,6 +26,12 @@ pub enum GfxError {
   Image(#[from] ImageError),
   #[error("{0}")]
   Draw(#[from] DrawError),
+  #[error("Static batch {0} was not found")]
+  StaticBatchNotFound(u64),
+  #[error("Failed to parse font on line {0}: {1}")]
+  FontParse(usize, String),
+  #[error("Font {0} has {1} pages, but {2} were given")]
+  FontPageCount(String, u32, usize),
   #[error("No active camera to render with")]
   NoActiveCamera,
 }
@@ -0,0 +1,486 @@
+use crate::{Color, GfxError, Point, Size, Text, TextAlign, TextureInfo};
+use rustc_hash::FxHashMap;
+
+/// The character drawn in place of characters a font doesn't have.
+const REPLACEMENT_CHARACTER: char = '?';
+
+/// A glyph of a bitmap font, in pixels.
+#[derive(Clone, Copy)]
+pub struct Glyph {
+  /// The left edge of the glyph in its page.
+  pub x: f32,
+  /// The top edge of the glyph in its page.
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+  /// The offset from the pen position to the left edge of the glyph.
+  pub x_offset: f32,
+  /// The offset from the top of the line to the top edge of the glyph.
+  pub y_offset: f32,
+  /// How far the pen moves after the glyph.
+  pub x_advance: f32,
+  /// The page the glyph is in.
+  pub page: u32,
+}
+
+/// A glyph laid out by `Font::layout`.
+pub struct PositionedGlyph {
+  /// The character id of the glyph.
+  pub id: u32,
+  /// The top-left corner of the glyph, relative to the top-left corner of
+  /// the text.
+  pub position: Point,
+  pub size: Size,
+  pub color: Color,
+}
+
+/// A bitmap font, whose metrics are read from an AngelCode BMFont `.fnt`
+/// file in the text format.
+pub struct Font {
+  /// The distance between the tops of two lines.
+  line_height: f32,
+  /// The distance from the top of a line to the baseline.
+  base: f32,
+  /// The width and height of the pages.
+  page_size: Size,
+  /// The number of pages.
+  pages: u32,
+  /// The glyphs, by character id.
+  glyphs: FxHashMap<u32, Glyph>,
+  /// The kerning amounts, by pair of character ids.
+  kernings: FxHashMap<(u32, u32), f32>,
+  /// The texture information of the glyphs whose pages have samplers, by
+  /// character id.
+  texture_infos: FxHashMap<u32, TextureInfo>,
+  /// The samplers of the pages that have them, by page.
+  page_samplers: FxHashMap<u32, u16>,
+}
+
+impl Font {
+  /// Parse a font from the contents of a `.fnt` file in the text format.
+  pub fn parse(source: &str) -> Result<Self, GfxError> {
+    let mut common = None;
+    let mut glyphs = FxHashMap::default();
+    let mut kernings = FxHashMap::default();
+    for (i, line) in source.lines().enumerate() {
+      let line_number = i + 1;
+      let (tag, attributes) = parse_line(line, line_number)?;
+      // Get a numeric attribute of the line.
+      let get = |key: &str| -> Result<f32, GfxError> {
+        let value = attributes
+          .iter()
+          .find(|(k, _)| *k == key)
+          .map(|(_, value)| *value)
+          .ok_or_else(|| GfxError::FontParse(line_number, format!("Missing {key}")))?;
+        value
+          .parse::<f32>()
+          .map_err(|_| GfxError::FontParse(line_number, format!("Invalid {key} {value:?}")))
+      };
+      match tag {
+        "common" => {
+          common.replace((
+            get("lineHeight")?,
+            get("base")?,
+            Size::new(get("scaleW")?, get("scaleH")?),
+            get("pages")? as u32,
+          ));
+        },
+        "char" => {
+          let glyph = Glyph {
+            x: get("x")?,
+            y: get("y")?,
+            width: get("width")?,
+            height: get("height")?,
+            x_offset: get("xoffset")?,
+            y_offset: get("yoffset")?,
+            x_advance: get("xadvance")?,
+            page: get("page")? as u32,
+          };
+          glyphs.insert(get("id")? as u32, glyph);
+        },
+        "kerning" => {
+          let pair = (get("first")? as u32, get("second")? as u32);
+          kernings.insert(pair, get("amount")?);
+        },
+        // The other tags, such as `info` and `page`, aren't needed.
+        _ => {},
+      }
+    }
+    let Some((line_height, base, page_size, pages)) = common else {
+      Err(GfxError::FontParse(0, "Missing common line".to_string()))?
+    };
+    Ok(Self {
+      line_height: line_height,
+      base: base,
+      page_size: page_size,
+      pages: pages,
+      glyphs: glyphs,
+      kernings: kernings,
+      texture_infos: FxHashMap::default(),
+      page_samplers: FxHashMap::default(),
+    })
+  }
+  /// Get the distance between the tops of two lines.
+  pub fn line_height(&self) -> f32 {
+    self.line_height
+  }
+  /// Get the distance from the top of a line to the baseline.
+  pub fn base(&self) -> f32 {
+    self.base
+  }
+  /// Get the number of pages.
+  pub fn pages(&self) -> u32 {
+    self.pages
+  }
+  /// Get the glyph of a character id.
+  pub fn glyph(&self, id: u32) -> Option<&Glyph> {
+    self.glyphs.get(&id)
+  }
+  /// Get the kerning amount between two character ids.
+  pub fn kerning(&self, first: u32, second: u32) -> f32 {
+    self.kernings.get(&(first, second)).copied().unwrap_or(0.0)
+  }
+  /// Get the textures of the glyphs in a page, as given to
+  /// `Renderer::add_sampler`. The textures are named after
+  /// `Font::texture_name`.
+  pub fn page_textures(&self, name: &str, page: u32) -> Vec<(String, Vec<[f32; 2]>)> {
+    self
+      .glyphs
+      .iter()
+      .filter(|(_, glyph)| glyph.page == page)
+      .map(|(&id, glyph)| (Self::texture_name(name, id), self.texture_coords(glyph)))
+      .collect()
+  }
+  /// Set the sampler of a page, caching the texture information of its
+  /// glyphs so they can be drawn without looking their textures up.
+  pub fn set_page_sampler(&mut self, page: u32, sampler_id: u16) {
+    let texture_infos = self
+      .glyphs
+      .iter()
+      .filter(|(_, glyph)| glyph.page == page)
+      .map(|(&id, glyph)| {
+        let texture_info = TextureInfo {
+          sampler_id: sampler_id,
+          texture_coords: self.texture_coords(glyph).into_boxed_slice(),
+        };
+        (id, texture_info)
+      })
+      .collect::<Vec<_>>();
+    self.texture_infos.extend(texture_infos);
+    self.page_samplers.insert(page, sampler_id);
+  }
+  /// Get the ids of the samplers of the pages that have them.
+  pub fn page_samplers(&self) -> impl Iterator<Item = u16> + '_ {
+    self.page_samplers.values().copied()
+  }
+  /// Get the texture information of the glyph of a character id, if its
+  /// page has a sampler.
+  pub fn texture_info(&self, id: u32) -> Option<&TextureInfo> {
+    self.texture_infos.get(&id)
+  }
+  /// Get the texture coordinates of the corners of a glyph in its page, in
+  /// the order of `Mesh::square`.
+  fn texture_coords(&self, glyph: &Glyph) -> Vec<[f32; 2]> {
+    let (w, h) = (self.page_size.w, self.page_size.h);
+    let (left, top) = (glyph.x / w, glyph.y / h);
+    let (right, bottom) = ((glyph.x + glyph.width) / w, (glyph.y + glyph.height) / h);
+    vec![[left, top], [right, top], [right, bottom], [left, bottom]]
+  }
+  /// Get the name of the texture of a glyph of a font.
+  pub fn texture_name(name: &str, id: u32) -> String {
+    format!("{name}#{id}")
+  }
+  /// Lay out the glyphs of text, in pixels from its top-left corner.
+  /// Glyphs are kerned, and if the text has a maximum width, lines are
+  /// wrapped between words, or within words that don't fit on a line of
+  /// their own. Characters the font doesn't have are replaced with `?`, or
+  /// skipped if it doesn't have that either.
+  pub fn layout(&self, text: &Text) -> Vec<PositionedGlyph> {
+    // Break the text into lines of glyphs, with their pen positions.
+    let mut lines = vec![Vec::<LaidOutGlyph>::new()];
+    // The pen position, previous character id, and index of the glyph after
+    // the last space of the current line.
+    let mut pen = 0.0;
+    let mut previous = None;
+    let mut wrap_index = None;
+    let characters = text
+      .sections
+      .iter()
+      .flat_map(|section| section.text.chars().map(|c| (c, section.color)));
+    for (character, color) in characters {
+      if character == '\n' {
+        lines.push(Vec::new());
+        pen = 0.0;
+        previous = None;
+        wrap_index = None;
+        continue;
+      }
+      // Get the glyph, or its replacement.
+      let Some((id, glyph)) = [character, REPLACEMENT_CHARACTER]
+        .into_iter()
+        .find_map(|c| self.glyph(c as u32).map(|glyph| (c as u32, glyph)))
+      else {
+        continue;
+      };
+      if let Some(previous) = previous {
+        pen += self.kerning(previous, id);
+      }
+      // Wrap the line if the glyph doesn't fit.
+      let line = lines.last_mut().unwrap();
+      let is_space = character.is_whitespace();
+      let overflows = text
+        .max_width
+        .is_some_and(|max_width| pen + glyph.x_offset + glyph.width > max_width);
+      if overflows && !is_space && !line.is_empty() {
+        // Move the last word to a new line, or just the glyph if the line
+        // has no space to wrap at.
+        let wrapped = line.split_off(wrap_index.unwrap_or(line.len()));
+        let shift = wrapped.first().map_or(pen, |first| first.pen);
+        let wrapped = wrapped
+          .into_iter()
+          .map(|laid_out| LaidOutGlyph {
+            pen: laid_out.pen - shift,
+            ..laid_out
+          })
+          .collect::<Vec<_>>();
+        pen -= shift;
+        lines.push(wrapped);
+        wrap_index = None;
+      }
+      // Add the glyph.
+      let line = lines.last_mut().unwrap();
+      line.push(LaidOutGlyph {
+        id: id,
+        glyph: glyph,
+        color: color,
+        pen: pen,
+        is_space: is_space,
+      });
+      if is_space {
+        wrap_index.replace(line.len());
+      }
+      pen += glyph.x_advance;
+      previous.replace(id);
+    }
+    // Get the width of each line, excluding trailing spaces.
+    let widths = lines
+      .iter()
+      .map(|line| {
+        line
+          .iter()
+          .rfind(|laid_out| !laid_out.is_space)
+          .map_or(0.0, |laid_out| laid_out.pen + laid_out.glyph.x_advance)
+      })
+      .collect::<Vec<_>>();
+    // Align the lines within the maximum width, or the widest line.
+    let width = text
+      .max_width
+      .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+    let mut positioned = Vec::new();
+    for (i, (line, line_width)) in lines.iter().zip(widths).enumerate() {
+      let offset = match text.align {
+        TextAlign::Left => 0.0,
+        TextAlign::Center => (width - line_width) / 2.0,
+        TextAlign::Right => width - line_width,
+      };
+      let top = i as f32 * self.line_height;
+      for laid_out in line {
+        let glyph = laid_out.glyph;
+        // Skip glyphs with nothing to draw, such as spaces.
+        if glyph.width <= 0.0 || glyph.height <= 0.0 {
+          continue;
+        }
+        positioned.push(PositionedGlyph {
+          id: laid_out.id,
+          position: Point::new(offset + laid_out.pen + glyph.x_offset, top + glyph.y_offset),
+          size: Size::new(glyph.width, glyph.height),
+          color: laid_out.color,
+        });
+      }
+    }
+    positioned
+  }
+}
+
+/// A glyph on a line, before the line is aligned.
+struct LaidOutGlyph<'a> {
+  id: u32,
+  glyph: &'a Glyph,
+  color: Color,
+  /// The pen position the glyph is drawn at.
+  pen: f32,
+  /// Whether the glyph is whitespace, which lines can be wrapped after.
+  is_space: bool,
+}
+
+/// The `key=value` attributes of a line of a `.fnt` file.
+type Attributes<'a> = Vec<(&'a str, &'a str)>;
+
+/// Parse a line of a `.fnt` file into its tag and attributes. Quoted values
+/// can contain spaces.
+fn parse_line(line: &str, line_number: usize) -> Result<(&str, Attributes<'_>), GfxError> {
+  let line = line.trim();
+  let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+  let mut attributes = Vec::new();
+  loop {
+    rest = rest.trim_start();
+    if rest.is_empty() {
+      break;
+    }
+    let Some((key, value)) = rest.split_once('=') else {
+      Err(GfxError::FontParse(
+        line_number,
+        format!("Expected key=value, found {rest:?}"),
+      ))?
+    };
+    // Read the value up to its closing quote, or the next whitespace.
+    let (value, remainder) = if let Some(quoted) = value.strip_prefix('"') {
+      quoted
+        .split_once('"')
+        .ok_or_else(|| GfxError::FontParse(line_number, format!("Unterminated value of {key}")))?
+    } else {
+      value.split_once(char::is_whitespace).unwrap_or((value, ""))
+    };
+    attributes.push((key, value));
+    rest = remainder;
+  }
+  Ok((tag, attributes))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A font whose glyphs are 8 pixels wide and advance by 10, with a
+  /// kerning pair between `A` and `V`.
+  const FNT: &str = r#"info face="Test Font" size=16
+common lineHeight=20 base=16 scaleW=128 scaleH=64 pages=1
+page id=0 file="test_0.png"
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=10 page=0
+char id=63 x=0 y=0 width=8 height=12 xoffset=1 yoffset=4 xadvance=10 page=0
+char id=65 x=8 y=0 width=8 height=12 xoffset=1 yoffset=4 xadvance=10 page=0
+char id=66 x=16 y=0 width=8 height=12 xoffset=1 yoffset=4 xadvance=10 page=0
+char id=86 x=24 y=0 width=8 height=12 xoffset=1 yoffset=4 xadvance=10 page=0
+kerning first=65 second=86 amount=-2
+"#;
+
+  fn font() -> Font {
+    Font::parse(FNT).unwrap()
+  }
+
+  fn layout(text: Text) -> Vec<(u32, Point)> {
+    font()
+      .layout(&text)
+      .into_iter()
+      .map(|glyph| (glyph.id, glyph.position))
+      .collect()
+  }
+
+  #[test]
+  fn fonts_are_parsed() {
+    let font = font();
+    assert_eq!(font.line_height(), 20.0);
+    assert_eq!(font.base(), 16.0);
+    assert_eq!(font.pages(), 1);
+    assert_eq!(font.glyph('B' as u32).unwrap().x, 16.0);
+    assert_eq!(font.kerning('A' as u32, 'V' as u32), -2.0);
+    assert_eq!(font.kerning('V' as u32, 'A' as u32), 0.0);
+    let textures = font.page_textures("test", 0);
+    let (_, coords) = textures
+      .iter()
+      .find(|(name, _)| *name == Font::texture_name("test", 'B' as u32))
+      .unwrap();
+    assert_eq!(coords[0], [0.125, 0.0]);
+    assert_eq!(coords[2], [0.1875, 0.1875]);
+    // The texture information is cached once the page has a sampler.
+    let mut font = font;
+    assert!(font.texture_info('B' as u32).is_none());
+    font.set_page_sampler(0, 3);
+    let texture_info = font.texture_info('B' as u32).unwrap();
+    assert_eq!(texture_info.sampler_id, 3);
+    assert_eq!(&texture_info.texture_coords[..], &coords[..]);
+  }
+
+  #[test]
+  fn invalid_fonts_are_rejected() {
+    let missing_common =
+      "char id=65 x=0 y=0 width=8 height=12 xoffset=0 yoffset=0 xadvance=10 page=0";
+    assert!(matches!(
+      Font::parse(missing_common),
+      Err(GfxError::FontParse(0, _))
+    ));
+    let invalid_value = "common lineHeight=20 base=16 scaleW=128 scaleH=x pages=1";
+    assert!(matches!(
+      Font::parse(invalid_value),
+      Err(GfxError::FontParse(1, _))
+    ));
+    let unterminated = "info face=\"Test\ncommon lineHeight=20 base=16 scaleW=1 scaleH=1 pages=1";
+    assert!(matches!(
+      Font::parse(unterminated),
+      Err(GfxError::FontParse(1, _))
+    ));
+  }
+
+  #[test]
+  fn glyphs_are_kerned_and_replaced() {
+    let glyphs = layout(Text::new("font", "AVé", Color::none()));
+    assert_eq!(
+      glyphs,
+      vec![
+        ('A' as u32, Point::new(1.0, 4.0)),
+        ('V' as u32, Point::new(9.0, 4.0)),
+        ('?' as u32, Point::new(19.0, 4.0)),
+      ]
+    );
+  }
+
+  #[test]
+  fn lines_are_wrapped_between_words() {
+    // "AB AB" is 50 pixels wide, so the second word is wrapped.
+    let text = Text::new("font", "AB AB", Color::none()).with_max_width(35.0);
+    let glyphs = layout(text);
+    let positions = glyphs
+      .iter()
+      .map(|(_, position)| *position)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      positions,
+      vec![
+        Point::new(1.0, 4.0),
+        Point::new(11.0, 4.0),
+        Point::new(1.0, 24.0),
+        Point::new(11.0, 24.0),
+      ]
+    );
+    // Words that don't fit on a line of their own are wrapped within.
+    let text = Text::new("font", "ABAB", Color::none()).with_max_width(25.0);
+    let lines = layout(text)
+      .iter()
+      .map(|(_, position)| position.y)
+      .collect::<Vec<_>>();
+    assert_eq!(lines, vec![4.0, 4.0, 24.0, 24.0]);
+  }
+
+  #[test]
+  fn lines_are_aligned() {
+    let text = |align| {
+      Text::new("font", "AB\nA", Color::none())
+        .with_section("B", Color::red())
+        .with_section("A", Color::none())
+        .with_align(align)
+    };
+    // The second line is "ABA", 30 pixels wide, and the first is 20.
+    let first_x = |align| layout(text(align))[0].1.x;
+    assert_eq!(first_x(TextAlign::Left), 1.0);
+    assert_eq!(first_x(TextAlign::Center), 6.0);
+    assert_eq!(first_x(TextAlign::Right), 11.0);
+    // Sections keep their colors across lines.
+    let colors = font()
+      .layout(&text(TextAlign::Left))
+      .into_iter()
+      .map(|glyph| <[f32; 4]>::from(glyph.color))
+      .collect::<Vec<_>>();
+    assert_eq!(colors[3], <[f32; 4]>::from(Color::red()));
+    assert_eq!(colors[4], <[f32; 4]>::from(Color::none()));
+  }
+}
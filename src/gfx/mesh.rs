@@ -1,40 +1,134 @@
-use crate::{Flag, Point};
+use crate::{triangulate, Flag, Point};
+use serde::{Deserialize, Serialize};
 
 /// A mesh.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "MeshData", into = "MeshData")]
 pub struct Mesh {
-  /// The vertices of the mesh.
+  /// The vertices of the mesh, which are the outline of a polygon followed
+  /// by its holes.
   vertices: Flag<Box<[Point]>>,
-  /// The indices of the mesh. Must be recalculated if
-  /// `vertices` is changed.
+  /// The indices the holes of the polygon start at.
+  holes: Box<[usize]>,
+  /// The indices of the mesh. Recalculated by triangulating the vertices
+  /// if `vertices` is changed.
   indices: Box<[u32]>,
+  /// The minimum and maximum corners of the bounding box of the
+  /// vertices.
+  bounds: (Point, Point),
+  /// The texture coordinates of the vertices, from 0 to 1 across the
+  /// texture, if they're set.
+  texture_coords: Option<Box<[[f32; 2]]>>,
 }
 
 impl Mesh {
-  /// Create a new mesh.
+  /// Create a new mesh with explicit indices.
   pub fn new(vertices: Vec<Point>, indices: Vec<u32>) -> Self {
     let vertices = vertices.into_boxed_slice();
     Self {
+      bounds: bounds(&vertices),
       vertices: Flag::new_clean(vertices),
+      holes: Box::new([]),
       indices: indices.into_boxed_slice(),
+      texture_coords: None,
     }
   }
-  /// Get the indices of the mesh.
+  /// Set the texture coordinates of the vertices, from 0 to 1 across the
+  /// texture. Without them, a texture is stretched across the bounding box
+  /// of the vertices.
+  pub fn with_texture_coords(mut self, texture_coords: Vec<[f32; 2]>) -> Self {
+    self
+      .texture_coords
+      .replace(texture_coords.into_boxed_slice());
+    self
+  }
+  /// Create a new mesh from the outline of a simple polygon, which can be
+  /// concave. The indices are calculated by triangulating the polygon.
+  pub fn polygon(outline: Vec<Point>) -> Self {
+    Self::polygon_with_holes(outline, Vec::new())
+  }
+  /// Create a new mesh from the outline of a simple polygon and its holes.
+  /// The vertices of the mesh are the outline followed by the holes. The
+  /// indices are calculated by triangulating the polygon.
+  pub fn polygon_with_holes(outline: Vec<Point>, holes: Vec<Vec<Point>>) -> Self {
+    let mut mesh = Self::new(Vec::new(), Vec::new());
+    mesh.set_polygon_with_holes(outline, holes);
+    mesh
+  }
+  /// Get the vertices of the mesh.
   pub fn vertices(&self) -> &[Point] {
     self.vertices.get().as_ref()
   }
+  /// Set the vertices of the mesh to the outline of a simple polygon. The
+  /// indices are recalculated the next time they're needed.
+  pub fn set_vertices(&mut self, vertices: Vec<Point>) {
+    self.set_polygon_with_holes(vertices, Vec::new());
+  }
+  /// Set the vertices of the mesh to the outline of a simple polygon and its
+  /// holes. The indices are recalculated the next time they're needed, and
+  /// the texture coordinates are unset.
+  pub fn set_polygon_with_holes(&mut self, outline: Vec<Point>, holes: Vec<Vec<Point>>) {
+    // Append the holes to the outline, keeping track of where they start.
+    let mut vertices = outline;
+    let mut starts = Vec::with_capacity(holes.len());
+    for hole in holes {
+      starts.push(vertices.len());
+      vertices.extend(hole);
+    }
+    let vertices = vertices.into_boxed_slice();
+    self.bounds = bounds(&vertices);
+    self.vertices.set(vertices);
+    self.holes = starts.into_boxed_slice();
+    self.texture_coords = None;
+  }
+  /// Get the indices the holes of the mesh start at.
+  pub fn holes(&self) -> &[usize] {
+    &self.holes
+  }
   /// Get the indices of the mesh.
   pub fn indices(&mut self) -> Box<[u32]> {
     // If the vertices are dirty, the indices must be recalculated
     // by triangulating the vertices.
     if self.vertices.is_dirty() {
-      // TODO:
-      // At the moment, vertices cannot be changed (otherwise this will
-      // panic).
-      todo!();
-      // self.vertices.clean();
+      self.indices = triangulate(self.vertices(), &self.holes).into_boxed_slice();
+      self.vertices.clean();
     }
     self.indices.clone()
   }
+  /// Get the minimum and maximum corners of the bounding box of the
+  /// vertices.
+  pub fn bounds(&self) -> (Point, Point) {
+    self.bounds
+  }
+  /// Get the texture coordinates of the vertices, if they're set.
+  pub fn texture_coords(&self) -> Option<&[[f32; 2]]> {
+    self.texture_coords.as_deref()
+  }
+  /// Get the texture coordinate of a vertex, from 0 to 1 across the
+  /// texture. Defaults to the position of the vertex in the bounding box of
+  /// the vertices.
+  pub fn texture_coord(&self, index: usize) -> [f32; 2] {
+    if let Some(texture_coord) = self
+      .texture_coords
+      .as_ref()
+      .and_then(|texture_coords| texture_coords.get(index))
+    {
+      return *texture_coord;
+    }
+    let (min, max) = self.bounds;
+    let vertex = self.vertices()[index];
+    let fraction = |value: f32, min: f32, max: f32| {
+      if max > min {
+        (value - min) / (max - min)
+      } else {
+        0.0
+      }
+    };
+    [
+      fraction(vertex.x, min.x, max.x),
+      fraction(vertex.y, min.y, max.y),
+    ]
+  }
   /// A unit square mesh.
   pub fn square() -> Self {
     Self::new(
@@ -48,3 +142,60 @@ impl Mesh {
     )
   }
 }
+
+/// Get the minimum and maximum corners of the bounding box of
+/// vertices. Zero if there are no vertices.
+fn bounds(vertices: &[Point]) -> (Point, Point) {
+  let Some(&first) = vertices.first() else {
+    return (Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+  };
+  vertices.iter().fold((first, first), |(min, max), vertex| {
+    (
+      Point::new(min.x.min(vertex.x), min.y.min(vertex.y)),
+      Point::new(max.x.max(vertex.x), max.y.max(vertex.y)),
+    )
+  })
+}
+
+/// The stored form of a mesh. If the indices are omitted, the vertices are
+/// triangulated.
+#[derive(Serialize, Deserialize)]
+struct MeshData {
+  vertices: Vec<Point>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  holes: Vec<usize>,
+  #[serde(default)]
+  indices: Vec<u32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  texture_coords: Option<Vec<[f32; 2]>>,
+}
+
+impl From<MeshData> for Mesh {
+  fn from(data: MeshData) -> Self {
+    if !data.indices.is_empty() {
+      let mut mesh = Self::new(data.vertices, data.indices);
+      mesh.holes = data.holes.into_boxed_slice();
+      mesh.texture_coords = data.texture_coords.map(Vec::into_boxed_slice);
+      return mesh;
+    }
+    let vertices = data.vertices.into_boxed_slice();
+    Self {
+      bounds: bounds(&vertices),
+      vertices: Flag::new_dirty(vertices),
+      holes: data.holes.into_boxed_slice(),
+      indices: Box::new([]),
+      texture_coords: data.texture_coords.map(Vec::into_boxed_slice),
+    }
+  }
+}
+
+impl From<Mesh> for MeshData {
+  fn from(mut mesh: Mesh) -> Self {
+    Self {
+      indices: mesh.indices().into_vec(),
+      holes: mesh.holes.to_vec(),
+      texture_coords: mesh.texture_coords.as_deref().map(<[_]>::to_vec),
+      vertices: mesh.vertices().to_vec(),
+    }
+  }
+}
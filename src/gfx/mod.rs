@@ -1,9 +1,15 @@
+pub mod batch;
 pub mod color;
+pub mod debug;
 pub mod error;
+pub mod font;
 pub mod mesh;
+pub mod overlay;
 pub mod pipeline;
 pub mod program;
 pub mod renderer;
 pub mod request;
+pub mod shape;
 pub mod texture;
+pub mod triangulate;
 pub mod vertex;
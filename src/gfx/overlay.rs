@@ -0,0 +1,121 @@
+use crate::{Color, Display, GfxError, Matrix4, Programs, Textures, Vertex, BLANK_SAMPLER_ID};
+use glium::{
+  index::PrimitiveType, uniform, uniforms::MagnifySamplerFilter, Blend, DrawParameters, Frame,
+  IndexBuffer, Rect, Surface, Texture2d, VertexBuffer,
+};
+
+/// An overlay drawn on top of a frame. Used for scene transitions.
+#[derive(Clone, Copy)]
+pub enum Overlay {
+  /// Fill the frame with a color.
+  Color(Color),
+  /// Draw the captured frame.
+  Capture {
+    /// The color the capture is multiplied by.
+    color: Color,
+    /// The offset of the capture, as a fraction of the frame size.
+    offset: [f32; 2],
+    /// The visible region of the frame as `[left, bottom, width, height]`,
+    /// as fractions of the frame size.
+    visible: [f32; 4],
+  },
+}
+
+/// Captures frames and draws overlays.
+pub struct Compositor {
+  vertex_buffer: VertexBuffer<Vertex>,
+  index_buffer: IndexBuffer<u32>,
+  capture: Option<Texture2d>,
+}
+
+impl Compositor {
+  /// Create a new compositor.
+  pub fn new(display: &Display) -> Result<Self, GfxError> {
+    Ok(Self {
+      vertex_buffer: VertexBuffer::empty_dynamic(display, 4)?,
+      index_buffer: IndexBuffer::immutable(
+        display,
+        PrimitiveType::TrianglesList,
+        &[0, 1, 2, 0, 2, 3],
+      )?,
+      capture: None,
+    })
+  }
+  /// Get whether a frame has been captured.
+  pub fn has_capture(&self) -> bool {
+    self.capture.is_some()
+  }
+  /// Capture the frame, replacing the previous capture.
+  pub fn capture(&mut self, display: &Display, frame: &Frame) -> Result<(), GfxError> {
+    let (width, height) = frame.get_dimensions();
+    let capture = Texture2d::empty(display, width, height)?;
+    frame.fill(&capture.as_surface(), MagnifySamplerFilter::Linear);
+    self.capture.replace(capture);
+    Ok(())
+  }
+  /// Discard the captured frame.
+  pub fn clear_capture(&mut self) {
+    self.capture.take();
+  }
+  /// Draw an overlay on top of the frame.
+  pub fn draw(
+    &mut self,
+    frame: &mut Frame,
+    programs: &Programs,
+    textures: &Textures,
+    overlay: Overlay,
+  ) -> Result<(), GfxError> {
+    // Determine the color, offset, visible region and sampler.
+    let (color, offset, visible, sampler) = match overlay {
+      Overlay::Color(color) => {
+        let sampler = textures.get_sampler(BLANK_SAMPLER_ID)?;
+        (color, [0.0, 0.0], [0.0, 0.0, 1.0, 1.0], sampler)
+      },
+      Overlay::Capture {
+        color,
+        offset,
+        visible,
+      } => {
+        // There is nothing to draw if no frame was captured.
+        let Some(capture) = &self.capture else {
+          return Ok(());
+        };
+        (color, offset, visible, capture)
+      },
+    };
+    // Write a quad covering the frame, in normalized device coordinates.
+    let color = color.into();
+    let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+    let vertices = corners.map(|[x, y]| Vertex {
+      position: [(x + offset[0]) * 2.0 - 1.0, (y + offset[1]) * 2.0 - 1.0],
+      color: color,
+      texture_coord: [x, y],
+    });
+    self.vertex_buffer.write(&vertices);
+    // Clip to the visible region.
+    let (width, height) = frame.get_dimensions();
+    let scissor = Rect {
+      left: (visible[0] * width as f32) as u32,
+      bottom: (visible[1] * height as f32) as u32,
+      width: (visible[2] * width as f32) as u32,
+      height: (visible[3] * height as f32) as u32,
+    };
+    // Draw the quad.
+    let projection: [[f32; 4]; 4] = Matrix4::identity().into();
+    frame.draw(
+      &self.vertex_buffer,
+      &self.index_buffer,
+      &programs.basic,
+      &uniform! {
+        u_projection: projection,
+        u_sampler: sampler,
+      },
+      &DrawParameters {
+        blend: Blend::alpha_blending(),
+        scissor: Some(scissor),
+        ..Default::default()
+      },
+    )?;
+    Ok(())
+  }
+}
@@ -1,4 +1,4 @@
-use crate::{Display, GfxError, Mesh, Point, Programs, Scale, TextureInfo, Textures, Vertex};
+use crate::{Display, GfxError, GlobalTransform, Mesh, Programs, TextureInfo, Textures, Vertex};
 use glium::{
   index::PrimitiveType, uniform, Blend, DrawParameters, Frame, IndexBuffer, Surface, VertexBuffer,
 };
@@ -27,13 +27,8 @@ impl Pipeline {
   ) -> Result<Self, GfxError> {
     // Get the flush threshold.
     let flush_threshold = flush_threshold.unwrap_or(DEFAULT_FLUSH_THRESHOLD);
-    // Determine the number of vertices of the mesh.
-    let vertices_per_mesh = {
-      let mut index_pattern = attributes.index_pattern.to_vec();
-      index_pattern.sort();
-      index_pattern.dedup();
-      index_pattern.len()
-    };
+    // Get the number of vertices of the mesh.
+    let vertices_per_mesh = attributes.vertex_count;
     // Generate the index data.
     let indices_per_mesh = attributes.index_pattern.len();
     let index_data = {
@@ -59,15 +54,14 @@ impl Pipeline {
       sampler_id: attributes.sampler_id,
     })
   }
-  /// Write to the pipeline.
+  /// Write to the pipeline, transforming the vertices of the mesh.
   pub fn write(
     &mut self,
     frame: &mut Frame,
     programs: &Programs,
     textures: &Textures,
     projection: &[[f32; 4]; 4],
-    position: Point,
-    scale: Scale,
+    transform: &GlobalTransform,
     color: [f32; 4],
     texture_info: &TextureInfo,
     mesh: &Mesh,
@@ -79,16 +73,13 @@ impl Pipeline {
     // Loop through the vertices.
     for (i, &vertex) in mesh.vertices().iter().enumerate() {
       // Calculate vertex information.
-      let vertex_position = {
-        let sv = vertex * scale;
-        Point::new(position.x + sv.x, position.y + sv.y)
-      };
+      let vertex_position = transform.transform_vertex(vertex);
       // Cache the vertex to write to.
       let vertex = &mut self.vertex_data[self.len * self.vertices_per_mesh + i];
       // Write to the cached vertex.
       vertex.position = vertex_position.into();
       vertex.color = color;
-      vertex.texture_coord = texture_info.texture_coords[i];
+      vertex.texture_coord = texture_info.texture_coord(mesh, i);
     }
     self.len += 1;
     Ok(())
@@ -138,8 +129,11 @@ impl Pipeline {
 }
 
 /// Attributes of a pipeline.
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PipelineAttributes {
   pub index_pattern: Box<[u32]>,
+  /// The number of vertices of the mesh, which can include vertices that
+  /// aren't indexed.
+  pub vertex_count: usize,
   pub sampler_id: u16,
 }
@@ -1,37 +1,146 @@
 use crate::{
-  Camera, Display, EngineError, GfxError, Pipeline, PipelineAttributes, Programs, Renderable,
-  Textures, Transform, World,
+  Camera, Color, Compositor, DebugDraw, Display, DynamicBatch, EngineError, Font, GfxError,
+  GlobalTransform, Mesh, Overlay, Owner, Pipeline, PipelineAttributes, Point, Programs, Renderable,
+  Scale, Size, StaticBatch, Text, TextureInfo, Textures, Transform, World,
 };
+use ahash::{AHashMap, AHashSet};
 use glium::Surface;
+use image::RgbaImage;
 use rustc_hash::FxHashMap;
+use std::{any::TypeId, collections::BTreeMap, mem};
 
-/// Renders to the display.
-pub struct Renderer {
+/// The number of frames a pipeline can go unused before it's dropped.
+const PIPELINE_IDLE_FRAMES: u64 = 300;
+
+/// The GL resources of a renderer.
+struct Backend {
   /// The GL context and facade.
   display: Display,
-  /// The pipelines managed by the renderer.
-  pipelines: FxHashMap<PipelineAttributes, Pipeline>,
   /// The program manager.
   programs: Programs,
+  /// The compositor.
+  compositor: Compositor,
+  /// The batch the debug shapes are drawn with.
+  debug_batch: DynamicBatch,
+}
+
+/// A pipeline managed by the renderer.
+struct ManagedPipeline {
+  /// The attributes the pipeline was created with.
+  attributes: PipelineAttributes,
+  /// The pipeline.
+  pipeline: Pipeline,
+  /// The frame the pipeline was last written to in.
+  last_used: u64,
+}
+
+/// Renders to the display.
+pub struct Renderer {
+  /// The GL resources. `None` if the renderer is headless.
+  backend: Option<Backend>,
+  /// The pipelines managed by the renderer, in the order they were created,
+  /// which is the order they're flushed in.
+  pipelines: Vec<ManagedPipeline>,
+  /// The indices of the pipelines, by their attributes.
+  pipeline_indices: FxHashMap<PipelineAttributes, usize>,
+  /// The number of frames executed.
+  frame_count: u64,
+  /// The static batches, by id.
+  static_batches: BTreeMap<u64, StaticBatch>,
+  /// The id of the next static batch.
+  next_static_batch_id: u64,
   /// The texture manager.
   textures: Textures,
+  /// The fonts, by name.
+  fonts: AHashMap<String, Font>,
+  /// The names of the fonts that text was drawn with before they were
+  /// added, so the text is only warned about once.
+  missing_fonts: AHashSet<String>,
+  /// The quad the glyphs of text are drawn with.
+  glyph_quad: Mesh,
   /// The render requests.
   render_requests: Vec<(Transform, Renderable)>,
+  /// The text render requests.
+  text_requests: Vec<(Transform, Text)>,
+  /// The debug shapes.
+  debug_draw: DebugDraw,
+  /// The overlay to draw on top of the next frame.
+  overlay: Option<Overlay>,
+  /// Whether to capture the next frame.
+  capture_requested: bool,
+  /// The scenes whose entities aren't rendered.
+  hidden_scenes: Vec<TypeId>,
 }
 
 impl Renderer {
   /// Create a new renderer.
   pub fn new(display: Display) -> Result<Self, GfxError> {
     let programs = Programs::new(&display)?;
-    let textures = Textures::new(&display)?;
+    let compositor = Compositor::new(&display)?;
+    let textures = Textures::new(Some(&display))?;
     Ok(Self {
-      display: display,
-      pipelines: FxHashMap::default(),
-      programs: programs,
+      backend: Some(Backend {
+        display: display,
+        programs: programs,
+        compositor: compositor,
+        debug_batch: DynamicBatch::new(),
+      }),
+      pipelines: Vec::new(),
+      pipeline_indices: FxHashMap::default(),
+      frame_count: 0,
+      static_batches: BTreeMap::new(),
+      next_static_batch_id: 0,
       textures: textures,
+      fonts: AHashMap::new(),
+      missing_fonts: AHashSet::new(),
+      glyph_quad: Mesh::square(),
+      render_requests: Vec::new(),
+      text_requests: Vec::new(),
+      debug_draw: DebugDraw::new(),
+      overlay: None,
+      capture_requested: false,
+      hidden_scenes: Vec::new(),
+    })
+  }
+  /// Create a new headless renderer. A headless renderer has no display,
+  /// so executing it only discards the render requests.
+  pub fn headless() -> Result<Self, GfxError> {
+    Ok(Self {
+      backend: None,
+      pipelines: Vec::new(),
+      pipeline_indices: FxHashMap::default(),
+      frame_count: 0,
+      static_batches: BTreeMap::new(),
+      next_static_batch_id: 0,
+      textures: Textures::new(None)?,
+      fonts: AHashMap::new(),
+      missing_fonts: AHashSet::new(),
+      glyph_quad: Mesh::square(),
       render_requests: Vec::new(),
+      text_requests: Vec::new(),
+      debug_draw: DebugDraw::new(),
+      overlay: None,
+      capture_requested: false,
+      hidden_scenes: Vec::new(),
+    })
+  }
+  /// Get whether the renderer is headless.
+  pub fn is_headless(&self) -> bool {
+    self.backend.is_none()
+  }
+  /// Get the frame buffer dimensions. Zero if the renderer is headless.
+  pub fn framebuffer_dimensions(&self) -> (u32, u32) {
+    self.backend.as_ref().map_or((0, 0), |backend| {
+      backend.display.get_framebuffer_dimensions()
     })
   }
+  /// Resize the display. `size` is the new size of the window, in
+  /// physical pixels.
+  pub fn resize(&self, size: (u32, u32)) {
+    if let Some(backend) = &self.backend {
+      backend.display.resize(size);
+    }
+  }
   /// Add a new sampler.
   /// Returns it's id.
   pub fn add_sampler(
@@ -39,16 +148,160 @@ impl Renderer {
     bytes: impl AsRef<[u8]>,
     info: impl IntoIterator<Item = (impl ToString, Vec<[f32; 2]>)>,
   ) -> Result<u16, GfxError> {
-    self.textures.add_sampler(&self.display, bytes, info)
+    let display = self.backend.as_ref().map(|backend| &backend.display);
+    self.textures.add_sampler(display, bytes, info)
+  }
+  /// Add a new sampler from an image decoded with
+  /// `Textures::decode_sampler`.
+  /// Returns it's id.
+  pub fn add_decoded_sampler(
+    &mut self,
+    image: RgbaImage,
+    info: impl IntoIterator<Item = (impl ToString, Vec<[f32; 2]>)>,
+  ) -> Result<u16, GfxError> {
+    let display = self.backend.as_ref().map(|backend| &backend.display);
+    self.textures.add_decoded_sampler(display, image, info)
+  }
+  /// Add a bitmap font, from the contents of its AngelCode BMFont `.fnt`
+  /// file and the images of its pages, in order. Each page is added with
+  /// `Renderer::add_sampler`, with a texture per glyph. A font previously
+  /// added under the name is replaced, and the samplers of its pages are
+  /// removed.
+  pub fn add_font(
+    &mut self,
+    name: impl ToString,
+    fnt: &str,
+    pages: impl IntoIterator<Item = impl AsRef<[u8]>>,
+  ) -> Result<(), GfxError> {
+    let name = name.to_string();
+    let mut font = Font::parse(fnt)?;
+    let pages = pages.into_iter().collect::<Vec<_>>();
+    if pages.len() != font.pages() as usize {
+      Err(GfxError::FontPageCount(
+        name.clone(),
+        font.pages(),
+        pages.len(),
+      ))?;
+    }
+    for (page, bytes) in pages.into_iter().enumerate() {
+      let sampler_id = self.add_sampler(bytes, font.page_textures(&name, page as u32))?;
+      font.set_page_sampler(page as u32, sampler_id);
+    }
+    self.missing_fonts.remove(&name);
+    if let Some(old_font) = self.fonts.insert(name, font) {
+      for sampler_id in old_font.page_samplers() {
+        self.textures.remove_sampler(sampler_id);
+      }
+    }
+    Ok(())
+  }
+  /// Get a font.
+  pub fn font(&self, name: &str) -> Option<&Font> {
+    self.fonts.get(name)
+  }
+  /// Add a static batch of renderables, such as level geometry. Its
+  /// vertices are transformed and uploaded once, and it's drawn every frame
+  /// in the layers of its renderables, below the other renderables of each
+  /// layer.
+  /// Returns its id.
+  pub fn add_static_batch(
+    &mut self,
+    renderables: impl IntoIterator<Item = (Transform, Renderable)>,
+  ) -> Result<u64, GfxError> {
+    let display = self.backend.as_ref().map(|backend| &backend.display);
+    let static_batch = StaticBatch::new(display, &self.textures, renderables)?;
+    let id = self.next_static_batch_id;
+    self.next_static_batch_id += 1;
+    self.static_batches.insert(id, static_batch);
+    Ok(id)
+  }
+  /// Replace the renderables of a static batch, keeping its id.
+  pub fn replace_static_batch(
+    &mut self,
+    id: u64,
+    renderables: impl IntoIterator<Item = (Transform, Renderable)>,
+  ) -> Result<(), GfxError> {
+    if !self.static_batches.contains_key(&id) {
+      Err(GfxError::StaticBatchNotFound(id))?;
+    }
+    let display = self.backend.as_ref().map(|backend| &backend.display);
+    let static_batch = StaticBatch::new(display, &self.textures, renderables)?;
+    self.static_batches.insert(id, static_batch);
+    Ok(())
+  }
+  /// Remove a static batch.
+  pub fn remove_static_batch(&mut self, id: u64) -> Result<(), GfxError> {
+    self
+      .static_batches
+      .remove(&id)
+      .ok_or(GfxError::StaticBatchNotFound(id))?;
+    Ok(())
   }
   /// Add a new render request.
   pub fn add_render_request(&mut self, request: (Transform, Renderable)) {
     self.render_requests.push(request);
   }
-  /// Execute the renderer.
+  /// Add a new text render request.
+  pub fn add_text_request(&mut self, request: (Transform, Text)) {
+    self.text_requests.push(request);
+  }
+  /// Get the debug drawer, whose shapes are drawn on top of the next frame.
+  pub fn debug_draw(&mut self) -> &mut DebugDraw {
+    &mut self.debug_draw
+  }
+  /// Set the overlay to draw on top of the next frame.
+  pub fn set_overlay(&mut self, overlay: Overlay) {
+    self.overlay.replace(overlay);
+  }
+  /// Capture the next frame, before its overlay is drawn. The capture can
+  /// then be drawn with `Overlay::Capture`.
+  pub fn request_capture(&mut self) {
+    self.capture_requested = true;
+  }
+  /// Set the scenes whose entities aren't rendered.
+  pub fn set_hidden_scenes(&mut self, hidden_scenes: Vec<TypeId>) {
+    self.hidden_scenes = hidden_scenes;
+  }
+  /// Get whether a frame has been captured.
+  pub fn has_capture(&self) -> bool {
+    self
+      .backend
+      .as_ref()
+      .is_some_and(|backend| backend.compositor.has_capture())
+  }
+  /// Discard the captured frame.
+  pub fn clear_capture(&mut self) {
+    if let Some(backend) = &mut self.backend {
+      backend.compositor.clear_capture();
+    }
+  }
+  /// Execute the renderer. Renderables are rendered by layer, from the
+  /// lowest to the highest. Within a layer, renderables are batched into
+  /// pipelines, which are flushed in the order they were created.
+  /// Pipelines that go unused for a while are dropped.
   pub fn execute(&mut self, world: &mut World) -> Result<(), EngineError> {
+    self.frame_count += 1;
+    let frame_count = self.frame_count;
+    // Take the overlay and capture request of this frame.
+    let overlay = self.overlay.take();
+    let capture_requested = mem::take(&mut self.capture_requested);
+    // Take the debug shapes of this frame.
+    let debug_shapes = self.debug_draw.take_shapes();
+    // Get the GL resources. If the renderer is headless, there is nothing
+    // to draw to, so just discard the render requests and debug shapes.
+    let Some(backend) = &mut self.backend else {
+      self.render_requests.clear();
+      self.text_requests.clear();
+      return Ok(());
+    };
+    let Backend {
+      display,
+      programs,
+      compositor,
+      debug_batch,
+    } = backend;
     // Get a frame and clear it.
-    let mut frame = self.display.draw();
+    let mut frame = display.draw();
     frame.clear_color(0.0, 0.0, 0.0, 0.0);
     // Catch the execution results. This is done because the frame MUST be
     // destroyed, even if the execution of the renderer fails.
@@ -57,56 +310,357 @@ impl Renderer {
       let projection = {
         // Get the active camera and inspect.
         let active_camera = world.actives.camera()?;
-        let (transform, camera) = world.standard_inspect::<(&Transform, &Camera)>(active_camera)?;
-        let fbd = self.display.get_framebuffer_dimensions();
-        camera.projection(fbd, transform.position)
+        let (transform, global_transform, camera) =
+          world
+            .standard_inspect::<(&Transform, Option<&GlobalTransform>, &Camera)>(active_camera)?;
+        let position = global_transform.map_or(transform.position, |global| global.position);
+        let fbd = display.get_framebuffer_dimensions();
+        camera.projection(fbd, position)
       };
-      // Query the renderables.
+      // Lay out the text of the entities and text render requests into
+      // glyphs, skipping entities of hidden scenes.
+      let hidden_scenes = &self.hidden_scenes;
+      let fonts = &self.fonts;
+      let missing_fonts = &mut self.missing_fonts;
+      let texts = world
+        .standard_query::<(&Transform, Option<&GlobalTransform>, &Text, Option<&Owner>)>()
+        .into_iter()
+        .filter(|(_, (_, _, _, owner))| {
+          owner.is_none_or(|owner| !hidden_scenes.contains(&owner.scene))
+        })
+        .flat_map(|(_, (transform, global_transform, text, _))| {
+          let global_transform = global_transform.copied().unwrap_or(transform.into());
+          Self::layout_text(fonts, missing_fonts, global_transform, text)
+        })
+        .collect::<Vec<_>>();
+      let text_requests = self.text_requests.iter().flat_map(|(transform, text)| {
+        Self::layout_text(fonts, missing_fonts, transform.into(), text)
+      });
+      let glyphs = texts.into_iter().chain(text_requests);
+      // Query the renderables, skipping those of hidden scenes.
+      // Entities are rendered at their global transforms.
       let query = world
-        .standard_query::<(&Transform, &mut Renderable)>()
+        .standard_query::<(
+          &Transform,
+          Option<&GlobalTransform>,
+          &mut Renderable,
+          Option<&Owner>,
+        )>()
         .into_iter()
-        .map(|(_, data)| data);
-      let requests = self.render_requests.iter_mut().map(|(t, r)| (&*t, r));
-      let chain = query.into_iter().chain(requests);
-      for (transform, renderable) in chain {
-        // Get the texture information of the renderable.
-        let texture_info = self.textures.get_texture_info(renderable.texture.get())?;
-        // Determine the pipeline attributes required to render the renderable.
-        let pipeline_attrs = PipelineAttributes {
-          index_pattern: renderable.mesh.indices(),
-          sampler_id: texture_info.sampler_id,
-        };
-        // Get the pipeline, or create it if necessary.
-        let pipeline = if let Some(pipeline) = self.pipelines.get_mut(&pipeline_attrs) {
-          pipeline
-        } else {
-          let pipeline = Pipeline::new(&self.display, &pipeline_attrs, None)?;
-          self.pipelines.entry(pipeline_attrs).or_insert(pipeline)
-        };
-        // Write to the pipeline.
-        pipeline.write(
-          &mut frame,
-          &self.programs,
-          &self.textures,
-          &projection,
-          transform.position,
-          transform.scale,
-          renderable.color.into(),
-          texture_info,
-          &renderable.mesh,
-        )?;
+        .filter(|(_, (_, _, _, owner))| {
+          owner.is_none_or(|owner| !hidden_scenes.contains(&owner.scene))
+        })
+        .map(|(_, (transform, global_transform, renderable, _))| {
+          let global_transform = global_transform.copied().unwrap_or(transform.into());
+          Drawable::Renderable(global_transform, renderable)
+        });
+      let requests = self
+        .render_requests
+        .iter_mut()
+        .map(|(t, r)| Drawable::Renderable(GlobalTransform::from(&*t), r));
+      // Sort the renderables by layer. The sort is stable, so within a layer
+      // entities come before render requests, then text.
+      let mut renderables = query
+        .into_iter()
+        .chain(requests)
+        .chain(glyphs)
+        .collect::<Vec<_>>();
+      renderables.sort_by_key(Drawable::layer);
+      // Get the layers to render, which are those of the renderables and the
+      // static batches.
+      let mut layers = renderables
+        .iter()
+        .map(Drawable::layer)
+        .chain(self.static_batches.values().flat_map(StaticBatch::layers))
+        .collect::<Vec<_>>();
+      layers.sort();
+      layers.dedup();
+      let mut renderables = renderables.into_iter().peekable();
+      let glyph_indices = self.glyph_quad.indices();
+      let glyph_quad = &self.glyph_quad;
+      for layer in layers {
+        // Draw the static batches first, so the renderables of the layer are
+        // drawn over them.
+        for static_batch in self.static_batches.values() {
+          static_batch.draw(&mut frame, programs, &self.textures, projection, layer)?;
+        }
+        // Write the renderables of the layer to the pipelines.
+        while let Some(drawable) = renderables.next_if(|drawable| drawable.layer() == layer) {
+          // Get the mesh, color and texture information of the renderable.
+          // Glyphs are drawn with the shared quad and their cached texture
+          // information.
+          let (transform, color, texture_info, mesh, index_pattern) = match drawable {
+            Drawable::Renderable(transform, renderable) => {
+              let texture_info = self.textures.get_texture_info(renderable.texture.get())?;
+              let index_pattern = renderable.mesh.indices();
+              (
+                transform,
+                renderable.color,
+                texture_info,
+                &renderable.mesh,
+                index_pattern,
+              )
+            },
+            Drawable::Glyph(glyph) => (
+              glyph.transform,
+              glyph.color,
+              glyph.texture_info,
+              glyph_quad,
+              glyph_indices.clone(),
+            ),
+          };
+          // Determine the pipeline attributes required to render the renderable.
+          let pipeline_attrs = PipelineAttributes {
+            index_pattern: index_pattern,
+            vertex_count: mesh.vertices().len(),
+            sampler_id: texture_info.sampler_id,
+          };
+          // Skip renderables with nothing to draw, such as degenerate polygons.
+          if pipeline_attrs.index_pattern.is_empty() {
+            continue;
+          }
+          // Get the pipeline, or create it if necessary.
+          let index = if let Some(&index) = self.pipeline_indices.get(&pipeline_attrs) {
+            index
+          } else {
+            self.pipelines.push(ManagedPipeline {
+              pipeline: Pipeline::new(display, &pipeline_attrs, None)?,
+              attributes: pipeline_attrs.clone(),
+              last_used: frame_count,
+            });
+            self
+              .pipeline_indices
+              .insert(pipeline_attrs, self.pipelines.len() - 1);
+            self.pipelines.len() - 1
+          };
+          let managed = &mut self.pipelines[index];
+          managed.last_used = frame_count;
+          let pipeline = &mut managed.pipeline;
+          // Write to the pipeline.
+          pipeline.write(
+            &mut frame,
+            programs,
+            &self.textures,
+            &projection,
+            &transform,
+            color.into(),
+            texture_info,
+            mesh,
+          )?;
+        }
+        // Flush the pipelines, so the layer is drawn below the next one.
+        for ManagedPipeline { pipeline, .. } in &mut self.pipelines {
+          pipeline.flush(&mut frame, programs, &self.textures, projection)?;
+        }
+      }
+      // Capture the frame if requested.
+      if capture_requested {
+        compositor.capture(display, &frame)?;
       }
-      // Loop through the pipelines, flushing them.
-      for pipeline in self.pipelines.values_mut() {
-        pipeline.flush(&mut frame, &self.programs, &self.textures, projection)?;
+      // Draw the overlay.
+      if let Some(overlay) = overlay {
+        compositor.draw(&mut frame, programs, &self.textures, overlay)?;
       }
+      // Draw the debug shapes on top of everything. Their meshes are
+      // different every frame, so they're drawn with a dynamic batch rather
+      // than through the pipelines.
+      debug_batch.draw(
+        display,
+        &mut frame,
+        programs,
+        &self.textures,
+        projection,
+        debug_shapes,
+      )?;
       Ok(())
     })();
     // Clear the render requests.
     self.render_requests.clear();
+    self.text_requests.clear();
+    // Drop the idle pipelines, so those of meshes that are no longer drawn
+    // don't pile up.
+    self.drop_idle_pipelines();
     // Finish the frame.
     frame.finish().map_err(GfxError::from)?;
     // Return the result of the execution.
     result
   }
+  /// Drop the pipelines that weren't written to in the last
+  /// `PIPELINE_IDLE_FRAMES` frames, keeping the others in order.
+  fn drop_idle_pipelines(&mut self) {
+    let frame_count = self.frame_count;
+    let len = self.pipelines.len();
+    self
+      .pipelines
+      .retain(|managed| frame_count - managed.last_used < PIPELINE_IDLE_FRAMES);
+    if self.pipelines.len() != len {
+      self.pipeline_indices = self
+        .pipelines
+        .iter()
+        .enumerate()
+        .map(|(index, managed)| (managed.attributes.clone(), index))
+        .collect();
+    }
+  }
+  /// Lay out text into glyphs, which are drawn with the glyph quad. Since
+  /// the glyphs share their quad, those of a page are batched into the same
+  /// pipeline. Text with a font that wasn't added is skipped, with a
+  /// warning the first time the font is missing.
+  fn layout_text<'a>(
+    fonts: &'a AHashMap<String, Font>,
+    missing_fonts: &mut AHashSet<String>,
+    transform: GlobalTransform,
+    text: &Text,
+  ) -> Vec<Drawable<'a>> {
+    let Some(font) = fonts.get(&text.font) else {
+      if missing_fonts.insert(text.font.clone()) {
+        log::warn!("Skipping text with font {}, which was not added", text.font);
+      }
+      return Vec::new();
+    };
+    font
+      .layout(text)
+      .into_iter()
+      .filter_map(|glyph| {
+        let texture_info = font.texture_info(glyph.id)?;
+        Some(Drawable::Glyph(DrawableGlyph {
+          transform: glyph_transform(transform, glyph.position, glyph.size),
+          color: glyph.color,
+          layer: text.layer,
+          texture_info: texture_info,
+        }))
+      })
+      .collect()
+  }
+}
+
+/// Something to draw in a layer.
+enum Drawable<'a> {
+  /// A renderable, at its global transform.
+  Renderable(GlobalTransform, &'a mut Renderable),
+  /// A glyph of text.
+  Glyph(DrawableGlyph<'a>),
+}
+
+impl Drawable<'_> {
+  /// Get the layer to draw in.
+  fn layer(&self) -> i32 {
+    match self {
+      Drawable::Renderable(_, renderable) => renderable.layer,
+      Drawable::Glyph(glyph) => glyph.layer,
+    }
+  }
+}
+
+/// A glyph of text, drawn with the glyph quad.
+struct DrawableGlyph<'a> {
+  /// The transform of the quad.
+  transform: GlobalTransform,
+  color: Color,
+  layer: i32,
+  /// The cached texture information of the glyph.
+  texture_info: &'a TextureInfo,
+}
+
+/// Get the transform of the unit quad of a glyph, from the transform of its
+/// text and its position and size relative to the text. The quad is scaled
+/// to the glyph and rotated around the text's origin.
+fn glyph_transform(text: GlobalTransform, position: Point, size: Size) -> GlobalTransform {
+  // The origin of the quad is the text's, relative to the glyph.
+  let origin = Point::new(
+    (text.origin.x - position.x) / size.w,
+    (text.origin.y - position.y) / size.h,
+  );
+  GlobalTransform {
+    position: text.pivot() - (text.origin - position) * text.scale,
+    scale: Scale::new(text.scale.x * size.w, text.scale.y * size.h),
+    rotation: text.rotation,
+    origin: origin,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::{ImageBuffer, ImageOutputFormat, Rgb};
+  use std::io::Cursor;
+
+  #[test]
+  fn glyph_quads_are_transformed_with_their_text() {
+    let text = GlobalTransform {
+      position: Point::new(100.0, 50.0),
+      scale: Scale::new(2.0, 3.0),
+      rotation: 0.7,
+      origin: Point::new(4.0, 1.0),
+    };
+    let (position, size) = (Point::new(10.0, 6.0), Size::new(5.0, 8.0));
+    let glyph = glyph_transform(text, position, size);
+    // The corners of the quad land where the corners of the glyph would
+    // within the text.
+    for corner in Mesh::square().vertices() {
+      let expected = text.transform_vertex(position + *corner * Scale::new(size.w, size.h));
+      let actual = glyph.transform_vertex(*corner);
+      assert!((expected.x - actual.x).abs() < 1e-3);
+      assert!((expected.y - actual.y).abs() < 1e-3);
+    }
+  }
+
+  #[test]
+  fn text_with_unknown_fonts_is_skipped() {
+    let text = Text::new("missing", "Hello", Color::none());
+    let fonts = AHashMap::new();
+    let mut missing_fonts = AHashSet::new();
+    let glyphs = Renderer::layout_text(
+      &fonts,
+      &mut missing_fonts,
+      GlobalTransform::default(),
+      &text,
+    );
+    assert!(glyphs.is_empty());
+    assert!(missing_fonts.contains("missing"));
+  }
+
+  #[test]
+  fn readded_fonts_replace_their_samplers() {
+    let fnt = |chars: &str| {
+      format!(
+        "common lineHeight=20 base=16 scaleW=16 scaleH=16 pages=1\n{}",
+        chars
+      )
+    };
+    let glyph = |id: u32| {
+      format!("char id={id} x=0 y=0 width=8 height=8 xoffset=0 yoffset=0 xadvance=8 page=0\n")
+    };
+    let image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(16, 16, Rgb([0, 0, 0]));
+    let mut page = Vec::new();
+    image
+      .write_to(&mut Cursor::new(&mut page), ImageOutputFormat::Png)
+      .unwrap();
+    let mut renderer = Renderer::headless().unwrap();
+    let first = fnt(&(glyph(65) + &glyph(66)));
+    renderer.add_font("test", &first, [&page]).unwrap();
+    let old_samplers = renderer
+      .font("test")
+      .unwrap()
+      .page_samplers()
+      .collect::<Vec<_>>();
+    // Drawing text with a font before it's added marks it as missing,
+    // until it's added.
+    renderer.missing_fonts.insert("test".to_string());
+    renderer
+      .add_font("test", &fnt(&glyph(65)), [&page])
+      .unwrap();
+    assert!(renderer.missing_fonts.is_empty());
+    // The textures of the old pages are gone, including those of glyphs
+    // the new font doesn't have.
+    let texture_info = renderer
+      .textures
+      .get_texture_info(&Font::texture_name("test", 65))
+      .unwrap();
+    assert!(!old_samplers.contains(&texture_info.sampler_id));
+    assert!(renderer
+      .textures
+      .get_texture_info(&Font::texture_name("test", 66))
+      .is_err());
+  }
 }
@@ -1,47 +1,60 @@
-use crate::{Color, Mesh, Point, Renderable, Scale, Texture, Transform};
+use crate::{
+  Color, DataError, Point, Prefab, PrefabOverrides, Renderable, Scale, Text, Texture, Transform,
+};
+use std::any::type_name;
 
 /// Render requests.
 pub struct RenderRequest;
 
 impl RenderRequest {
-  /// A point.
-  // !TODO:
-  // The point is currently actually a small square instead of a circle.
-  // Change to a circle mesh.
+  /// A render request from a prefab with a transform and renderable.
+  /// Returns `DataError::ComponentNotFound` if the prefab lacks either of
+  /// them.
+  pub fn prefab(
+    prefab: &Prefab,
+    overrides: PrefabOverrides,
+  ) -> Result<(Transform, Renderable), DataError> {
+    let mut prefab = prefab.instantiate(overrides);
+    Ok((Self::take(&mut prefab)?, Self::take(&mut prefab)?))
+  }
+  /// A point, which is a small circle centered on its position. It's the
+  /// built-in `point` prefab.
   pub fn point(position: impl Into<Point>, color: Color) -> (Transform, Renderable) {
-    let mesh = Mesh::new(
-      vec![
-        Point::new(-0.5, -0.5),
-        Point::new(0.5, -0.5),
-        Point::new(0.5, 0.5),
-        Point::new(-0.5, 0.5),
-      ],
-      vec![0, 2, 1, 0, 3, 2],
-    );
-    (
-      Transform::new(position, [8.0, 8.0]),
-      Renderable::new(color, Texture::none(), mesh),
-    )
+    let overrides = PrefabOverrides::new().position(position).color(color);
+    Self::built_in(&Prefab::point(), overrides)
   }
-  /// A square.
+  /// A square, whose origin is its top-left corner. It's the built-in
+  /// `square` prefab.
   pub fn square(
     position: impl Into<Point>,
     scale: impl Into<Scale>,
     color: Color,
     texture: Texture,
   ) -> (Transform, Renderable) {
-    let mesh = Mesh::new(
-      vec![
-        Point::new(0.0, 0.0),
-        Point::new(1.0, 0.0),
-        Point::new(1.0, 1.0),
-        Point::new(0.0, 1.0),
-      ],
-      vec![0, 2, 1, 0, 3, 2],
-    );
-    (
-      Transform::new(position, scale),
-      Renderable::new(color, texture, mesh),
-    )
+    let overrides = PrefabOverrides::new()
+      .position(position)
+      .scale(scale)
+      .color(color);
+    let (transform, mut renderable) = Self::built_in(&Prefab::square(), overrides);
+    renderable.texture = texture;
+    (transform, renderable)
+  }
+  /// Text, whose top-left corner is at `position`. It's drawn with
+  /// `Renderer::add_text_request`.
+  pub fn text(position: impl Into<Point>, text: Text) -> (Transform, Text) {
+    (Transform::new(position, [1.0, 1.0]), text)
+  }
+  /// A render request from a built-in prefab.
+  fn built_in(prefab: &Prefab, overrides: PrefabOverrides) -> (Transform, Renderable) {
+    match Self::prefab(prefab, overrides) {
+      Ok(request) => request,
+      Err(_) => unreachable!("the built-in prefabs have a transform and renderable"),
+    }
+  }
+  /// Take a component out of a prefab.
+  fn take<T: 'static>(prefab: &mut Prefab) -> Result<T, DataError> {
+    prefab
+      .take()
+      .ok_or(DataError::ComponentNotFound(type_name::<T>()))
   }
 }
@@ -0,0 +1,259 @@
+use crate::{Mesh, Point, Size};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// The number of segments per half turn of a round line join.
+const ROUND_JOIN_SEGMENTS: f32 = 8.0;
+
+/// The farthest a miter can reach from the middle of the line, relative to
+/// half of its thickness, before it's beveled instead.
+const MITER_LIMIT: f32 = 2.0;
+
+/// How the segments of a polyline are joined.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum LineJoin {
+  /// The outer edges are extended until they meet. Joins that are too
+  /// sharp are beveled instead.
+  #[default]
+  Miter,
+  /// The outer corners are connected with a straight edge.
+  Bevel,
+  /// The outer corners are connected with an arc.
+  Round,
+}
+
+/// Builds the vertices, indices and texture coordinates of a shape.
+#[derive(Default)]
+struct ShapeBuilder {
+  vertices: Vec<Point>,
+  indices: Vec<u32>,
+  texture_coords: Vec<[f32; 2]>,
+}
+
+impl ShapeBuilder {
+  /// Add a vertex.
+  /// Returns its index.
+  fn vertex(&mut self, vertex: Point, texture_coord: [f32; 2]) -> u32 {
+    self.vertices.push(vertex);
+    self.texture_coords.push(texture_coord);
+    self.vertices.len() as u32 - 1
+  }
+  /// Add a vertex, whose texture coordinate is its position in a bounding
+  /// box.
+  fn boxed_vertex(&mut self, vertex: Point, min: Point, max: Point) -> u32 {
+    let fraction = |value: f32, min: f32, max: f32| {
+      if max > min {
+        (value - min) / (max - min)
+      } else {
+        0.0
+      }
+    };
+    let texture_coord = [
+      fraction(vertex.x, min.x, max.x),
+      fraction(vertex.y, min.y, max.y),
+    ];
+    self.vertex(vertex, texture_coord)
+  }
+  /// Add a triangle.
+  fn triangle(&mut self, a: u32, b: u32, c: u32) {
+    self.indices.extend([a, b, c]);
+  }
+  /// Add a quad, whose vertices are in order around it.
+  fn quad(&mut self, a: u32, b: u32, c: u32, d: u32) {
+    self.indices.extend([a, b, c, a, c, d]);
+  }
+  /// Build the mesh.
+  fn build(self) -> Mesh {
+    Mesh::new(self.vertices, self.indices).with_texture_coords(self.texture_coords)
+  }
+}
+
+/// Get the point at an angle on an ellipse centered on the origin.
+fn on_ellipse(radii: Size, angle: f32) -> Point {
+  Point::new(radii.w * angle.cos(), radii.h * angle.sin())
+}
+
+impl Mesh {
+  /// A circle centered on the origin, with at least three segments.
+  pub fn circle(radius: f32, segments: u32) -> Self {
+    Self::ellipse(Size::new(radius, radius), segments)
+  }
+  /// An ellipse centered on the origin, with at least three segments.
+  pub fn ellipse(radii: impl Into<Size>, segments: u32) -> Self {
+    let radii = radii.into();
+    let segments = segments.max(3);
+    let (min, max) = (Point::new(-radii.w, -radii.h), Point::new(radii.w, radii.h));
+    // Fan out from the center.
+    let mut builder = ShapeBuilder::default();
+    let center = builder.boxed_vertex(Point::new(0.0, 0.0), min, max);
+    for i in 0..segments {
+      let angle = TAU * i as f32 / segments as f32;
+      builder.boxed_vertex(on_ellipse(radii, angle), min, max);
+      builder.triangle(center, center + 1 + i, center + 1 + (i + 1) % segments);
+    }
+    builder.build()
+  }
+  /// A regular polygon centered on the origin, with at least three sides
+  /// and a vertex pointing up.
+  pub fn regular_polygon(radius: f32, sides: u32) -> Self {
+    let sides = sides.max(3);
+    let (min, max) = (Point::new(-radius, -radius), Point::new(radius, radius));
+    // Fan out from the first vertex, since the polygon is convex.
+    let mut builder = ShapeBuilder::default();
+    for i in 0..sides {
+      let angle = -FRAC_PI_2 + TAU * i as f32 / sides as f32;
+      builder.boxed_vertex(on_ellipse(Size::new(radius, radius), angle), min, max);
+    }
+    for i in 1..sides - 1 {
+      builder.triangle(0, i, i + 1);
+    }
+    builder.build()
+  }
+  /// A rectangle with rounded corners, whose origin is its top-left
+  /// corner. The radius is clamped to half of the shorter side, and each
+  /// corner has at least one segment.
+  pub fn rounded_rect(size: impl Into<Size>, radius: f32, segments: u32) -> Self {
+    let size = size.into();
+    let radius = radius.clamp(0.0, size.w.min(size.h) / 2.0);
+    let segments = segments.max(1);
+    let (min, max) = (Point::new(0.0, 0.0), Point::new(size.w, size.h));
+    // The centers of the corners, clockwise from the top-right.
+    let corners = [
+      Point::new(size.w - radius, radius),
+      Point::new(size.w - radius, size.h - radius),
+      Point::new(radius, size.h - radius),
+      Point::new(radius, radius),
+    ];
+    // Fan out from the center, around the corners.
+    let mut builder = ShapeBuilder::default();
+    let center = builder.boxed_vertex(Point::new(size.w / 2.0, size.h / 2.0), min, max);
+    for (i, corner) in corners.into_iter().enumerate() {
+      let start = -FRAC_PI_2 + FRAC_PI_2 * i as f32;
+      for j in 0..=segments {
+        let angle = start + FRAC_PI_2 * j as f32 / segments as f32;
+        let vertex = corner + on_ellipse(Size::new(radius, radius), angle);
+        builder.boxed_vertex(vertex, min, max);
+      }
+    }
+    let rim = builder.vertices.len() as u32 - 1;
+    for i in 0..rim {
+      builder.triangle(center, center + 1 + i, center + 1 + (i + 1) % rim);
+    }
+    builder.build()
+  }
+  /// A thick arc of a circle centered on the origin, from `start` to `end`
+  /// clockwise, in radians. The radius is the middle of the arc. If
+  /// the arc is as thick as its diameter, it's a sector. The texture runs
+  /// along the arc, from the inside to the outside.
+  pub fn arc(radius: f32, thickness: f32, start: f32, end: f32, segments: u32) -> Self {
+    let segments = segments.max(1);
+    let inner = (radius - thickness / 2.0).max(0.0);
+    let outer = radius + thickness / 2.0;
+    // Build a strip of quads between the inner and outer edges.
+    let mut builder = ShapeBuilder::default();
+    for i in 0..=segments {
+      let fraction = i as f32 / segments as f32;
+      let angle = start + (end - start) * fraction;
+      builder.vertex(on_ellipse(Size::new(inner, inner), angle), [fraction, 0.0]);
+      builder.vertex(on_ellipse(Size::new(outer, outer), angle), [fraction, 1.0]);
+      if i > 0 {
+        let base = 2 * i;
+        builder.quad(base - 2, base, base + 1, base - 1);
+      }
+    }
+    builder.build()
+  }
+  /// A thick line segment. The texture runs along the line, and across its
+  /// thickness.
+  pub fn line(start: impl Into<Point>, end: impl Into<Point>, thickness: f32) -> Self {
+    Self::polyline(&[start.into(), end.into()], thickness, LineJoin::Bevel)
+  }
+  /// A thick line through points, whose segments are joined with `join`.
+  /// The texture runs along the line, and across its thickness.
+  /// Consecutive duplicate points are skipped.
+  pub fn polyline(points: &[Point], thickness: f32, join: LineJoin) -> Self {
+    let half = thickness / 2.0;
+    let mut points = points.to_vec();
+    points.dedup();
+    // Get the length along the line of each point, for the texture.
+    let mut lengths = vec![0.0; points.len()];
+    for i in 1..points.len() {
+      let (a, b) = (points[i - 1], points[i]);
+      lengths[i] = lengths[i - 1] + (b.x - a.x).hypot(b.y - a.y);
+    }
+    let total = lengths.last().copied().unwrap_or(0.0).max(f32::EPSILON);
+    // Get the unit normal of each segment.
+    let normals = points
+      .windows(2)
+      .map(|segment| {
+        let (dx, dy) = (segment[1].x - segment[0].x, segment[1].y - segment[0].y);
+        let length = dx.hypot(dy);
+        Point::new(-dy / length, dx / length)
+      })
+      .collect::<Vec<_>>();
+    let offset = |point: Point, normal: Point, distance: f32| {
+      Point::new(point.x + normal.x * distance, point.y + normal.y * distance)
+    };
+    let mut builder = ShapeBuilder::default();
+    // Build a quad per segment.
+    for (i, &normal) in normals.iter().enumerate() {
+      let (u0, u1) = (lengths[i] / total, lengths[i + 1] / total);
+      let a = builder.vertex(offset(points[i], normal, -half), [u0, 0.0]);
+      let b = builder.vertex(offset(points[i + 1], normal, -half), [u1, 0.0]);
+      let c = builder.vertex(offset(points[i + 1], normal, half), [u1, 1.0]);
+      let d = builder.vertex(offset(points[i], normal, half), [u0, 1.0]);
+      builder.quad(a, b, c, d);
+    }
+    // Join the segments, filling the gap on the outer side of each turn.
+    for (i, normals) in normals.windows(2).enumerate() {
+      let (n0, n1) = (normals[0], normals[1]);
+      let point = points[i + 1];
+      let u = lengths[i + 1] / total;
+      // The gap is on the opposite side of the normals to the turn.
+      let turn = n0.x * n1.y - n0.y * n1.x;
+      if turn == 0.0 {
+        continue;
+      }
+      let (side, v) = if turn > 0.0 {
+        (-half, 0.0)
+      } else {
+        (half, 1.0)
+      };
+      let center = builder.vertex(point, [u, 0.5]);
+      let from = builder.vertex(offset(point, n0, side), [u, v]);
+      let to = builder.vertex(offset(point, n1, side), [u, v]);
+      // Get the miter, which is along the sum of the normals.
+      let sum = Point::new(n0.x + n1.x, n0.y + n1.y);
+      let cos = (sum.x.hypot(sum.y) / 2.0).max(f32::EPSILON);
+      let miter_length = 1.0 / cos;
+      match join {
+        LineJoin::Miter if miter_length <= MITER_LIMIT => {
+          let direction = Point::new(sum.x / (2.0 * cos), sum.y / (2.0 * cos));
+          let miter = builder.vertex(offset(point, direction, side * miter_length), [u, v]);
+          builder.quad(center, from, miter, to);
+        },
+        LineJoin::Round => {
+          let start = (n0.y * side.signum()).atan2(n0.x * side.signum());
+          let mut sweep = (n1.y * side.signum()).atan2(n1.x * side.signum()) - start;
+          if sweep > PI {
+            sweep -= TAU;
+          } else if sweep < -PI {
+            sweep += TAU;
+          }
+          let segments = (sweep.abs() / PI * ROUND_JOIN_SEGMENTS).ceil().max(1.0) as u32;
+          let mut previous = from;
+          for j in 1..segments {
+            let angle = start + sweep * j as f32 / segments as f32;
+            let direction = on_ellipse(Size::new(half, half), angle);
+            let next = builder.vertex(point + direction, [u, v]);
+            builder.triangle(center, previous, next);
+            previous = next;
+          }
+          builder.triangle(center, previous, to);
+        },
+        _ => builder.triangle(center, from, to),
+      }
+    }
+    builder.build()
+  }
+}
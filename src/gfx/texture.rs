@@ -1,10 +1,15 @@
-use crate::{Display, GfxError};
+use crate::{Display, GfxError, Mesh};
 use ahash::AHashMap;
 use glium::{texture::RawImage2d, Texture2d};
-use image::{ImageBuffer, ImageOutputFormat, Rgb};
+use image::{ImageBuffer, ImageOutputFormat, Rgb, RgbaImage};
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 
+/// The id of the blank sampler, which is added when the texture manager is
+/// created.
+pub const BLANK_SAMPLER_ID: u16 = 0;
+
 /// Manages textures.
 pub struct Textures {
   textures: AHashMap<String, TextureInfo>,
@@ -14,14 +19,17 @@ pub struct Textures {
 
 impl Textures {
   /// Create a new texture manager.
-  pub fn new(display: &Display) -> Result<Self, GfxError> {
+  /// If `display` is `None`, textures are registered without creating
+  /// their samplers.
+  pub fn new(display: Option<&Display>) -> Result<Self, GfxError> {
     // Create the texture manager.
     let mut textures = Self {
       textures: AHashMap::new(),
       samplers: FxHashMap::default(),
       next_sampler_id: 0,
     };
-    // Add a blank sampler.
+    // Add a blank sampler. Since it is the first sampler, its id is
+    // `BLANK_SAMPLER_ID`.
     let image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(1, 1, Rgb([255, 255, 255]));
     let mut bytes = Vec::new();
     let mut cursor = Cursor::new(&mut bytes);
@@ -49,9 +57,25 @@ impl Textures {
   /// Returns it's id.
   pub fn add_sampler(
     &mut self,
-    display: &Display,
+    display: Option<&Display>,
     bytes: impl AsRef<[u8]>,
     info: impl IntoIterator<Item = (impl ToString, Vec<[f32; 2]>)>,
+  ) -> Result<u16, GfxError> {
+    let image = Self::decode_sampler(bytes)?;
+    self.add_decoded_sampler(display, image, info)
+  }
+  /// Decode the image of a sampler. This doesn't touch the GL context, so
+  /// it can be done on any thread.
+  pub fn decode_sampler(bytes: impl AsRef<[u8]>) -> Result<RgbaImage, GfxError> {
+    Ok(image::load_from_memory(bytes.as_ref())?.to_rgba8())
+  }
+  /// Add a new sampler from a decoded image.
+  /// Returns it's id.
+  pub fn add_decoded_sampler(
+    &mut self,
+    display: Option<&Display>,
+    image: RgbaImage,
+    info: impl IntoIterator<Item = (impl ToString, Vec<[f32; 2]>)>,
   ) -> Result<u16, GfxError> {
     // Generate a sampler id.
     let sampler_id = self.next_sampler_id;
@@ -59,14 +83,13 @@ impl Textures {
     // Create the sampler. Note that this should be done prior to adding
     // the texture information, since if the sampler cannot be created, there
     // should not be textures added.
-    let sampler = {
-      let image = image::load_from_memory(bytes.as_ref())?.to_rgba8();
+    if let Some(display) = display {
       let dimensions = image.dimensions();
       let raw = RawImage2d::from_raw_rgba(image.into_raw(), dimensions);
-      Texture2d::new(display, raw)?
-    };
-    // Add the sampler.
-    self.samplers.insert(sampler_id, sampler);
+      let sampler = Texture2d::new(display, raw)?;
+      // Add the sampler.
+      self.samplers.insert(sampler_id, sampler);
+    }
     // Loop through info and add the textures.
     for (texture, texture_coords) in info.into_iter() {
       let texture_info = TextureInfo {
@@ -77,6 +100,13 @@ impl Textures {
     }
     Ok(sampler_id)
   }
+  /// Remove a sampler and the textures in it.
+  pub fn remove_sampler(&mut self, id: u16) {
+    self.samplers.remove(&id);
+    self
+      .textures
+      .retain(|_, texture_info| texture_info.sampler_id != id);
+  }
   /// Get a sampler from it's id.
   #[inline]
   pub fn get_sampler(&self, id: u16) -> Result<&Texture2d, GfxError> {
@@ -90,20 +120,41 @@ pub struct TextureInfo {
   pub texture_coords: Box<[[f32; 2]]>,
 }
 
+impl TextureInfo {
+  /// Get the texture coordinate of a vertex of a mesh. If the mesh has no
+  /// texture coordinates of its own and the texture has a coordinate per
+  /// vertex of the mesh, it's used as is. Otherwise the first four
+  /// coordinates are the corners of the texture, in the order of
+  /// `Mesh::square`, which are interpolated at the mesh's texture
+  /// coordinate.
+  pub fn texture_coord(&self, mesh: &Mesh, index: usize) -> [f32; 2] {
+    let coords = &self.texture_coords;
+    let per_vertex = mesh.texture_coords().is_none() && coords.len() == mesh.vertices().len();
+    if per_vertex || coords.len() < 4 {
+      return coords.get(index).copied().unwrap_or_default();
+    }
+    // Interpolate the corners bilinearly.
+    let [u, v] = mesh.texture_coord(index);
+    let lerp =
+      |a: [f32; 2], b: [f32; 2], t: f32| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+    lerp(
+      lerp(coords[0], coords[1], u),
+      lerp(coords[3], coords[2], u),
+      v,
+    )
+  }
+}
+
 /// A texture.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum Texture {
   /// No texture. Equivalent to `Texture::Regular("")`.
+  #[default]
   None,
   /// A regular texture.
   Regular(String),
 }
 
-impl Default for Texture {
-  fn default() -> Self {
-    Self::None
-  }
-}
-
 impl Texture {
   /// Create a new blank texture.
   pub fn none() -> Self {
@@ -0,0 +1,320 @@
+use crate::Point;
+
+/// Triangulate a polygon with holes by ear clipping. `vertices` holds the
+/// outline of the polygon followed by its holes, and `holes` holds the
+/// indices the holes start at. The outline and holes can be concave and in
+/// either winding order. Holes outside of the outline are ignored, and
+/// self-intersecting polygons are triangulated on a best-effort basis.
+/// Returns the indices of the triangles.
+pub fn triangulate(vertices: &[Point], holes: &[usize]) -> Vec<u32> {
+  // Split the vertices into the outline and the holes.
+  let mut bounds = holes
+    .iter()
+    .copied()
+    .filter(|&start| start > 0 && start < vertices.len())
+    .collect::<Vec<_>>();
+  bounds.sort_unstable();
+  bounds.dedup();
+  bounds.insert(0, 0);
+  bounds.push(vertices.len());
+  let mut rings = bounds
+    .windows(2)
+    .map(|window| (window[0]..window[1]).collect::<Vec<_>>())
+    .filter(|ring| ring.len() >= 3);
+  let Some(mut polygon) = rings.next() else {
+    return Vec::new();
+  };
+  // Wind the outline counterclockwise and the holes clockwise.
+  if signed_area(vertices, &polygon) < 0.0 {
+    polygon.reverse();
+  }
+  let mut holes = rings
+    .map(|mut ring| {
+      if signed_area(vertices, &ring) > 0.0 {
+        ring.reverse();
+      }
+      ring
+    })
+    .collect::<Vec<_>>();
+  // Bridge the holes into the outline, from the rightmost hole leftwards.
+  holes.sort_by(|a, b| max_x(vertices, b).total_cmp(&max_x(vertices, a)));
+  for hole in holes {
+    bridge_hole(vertices, &mut polygon, &hole);
+  }
+  // Clip the ears of the polygon.
+  clip_ears(vertices, polygon)
+}
+
+/// Get the signed area of a ring, which is positive if it's wound
+/// counterclockwise.
+fn signed_area(vertices: &[Point], ring: &[usize]) -> f32 {
+  let mut area = 0.0;
+  for (i, &a) in ring.iter().enumerate() {
+    let b = ring[(i + 1) % ring.len()];
+    area += vertices[a].x * vertices[b].y - vertices[b].x * vertices[a].y;
+  }
+  area / 2.0
+}
+
+/// Get the greatest x coordinate of a ring.
+fn max_x(vertices: &[Point], ring: &[usize]) -> f32 {
+  ring
+    .iter()
+    .map(|&i| vertices[i].x)
+    .fold(f32::NEG_INFINITY, f32::max)
+}
+
+/// Get the cross product of the edges `a -> b` and `b -> c`, which is
+/// positive if they turn counterclockwise.
+fn cross(a: Point, b: Point, c: Point) -> f32 {
+  (b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x)
+}
+
+/// Get whether a point is inside of or on a counterclockwise triangle.
+fn in_triangle(point: Point, a: Point, b: Point, c: Point) -> bool {
+  cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
+
+/// Splice a clockwise hole into a counterclockwise polygon, through a pair
+/// of coincident edges between the hole's rightmost vertex and a vertex of
+/// the polygon that is visible from it.
+fn bridge_hole(vertices: &[Point], polygon: &mut Vec<usize>, hole: &[usize]) {
+  // Find the rightmost vertex of the hole.
+  let Some(m_pos) = (0..hole.len()).max_by(|&a, &b| {
+    vertices[hole[a]]
+      .x
+      .total_cmp(&vertices[hole[b]].x)
+      .then(vertices[hole[b]].y.total_cmp(&vertices[hole[a]].y))
+  }) else {
+    return;
+  };
+  let m = vertices[hole[m_pos]];
+  // Cast a ray to the right of it, finding the closest edge of the polygon
+  // it hits. The candidate is the rightmost endpoint of that edge.
+  let mut closest = None::<(f32, usize)>;
+  for i in 0..polygon.len() {
+    let a = vertices[polygon[i]];
+    let b = vertices[polygon[(i + 1) % polygon.len()]];
+    if (a.y > m.y) == (b.y > m.y) || a.y == b.y {
+      continue;
+    }
+    let x = a.x + (m.y - a.y) * (b.x - a.x) / (b.y - a.y);
+    if x < m.x || closest.is_some_and(|(closest_x, _)| x >= closest_x) {
+      continue;
+    }
+    let candidate = if a.x > b.x {
+      i
+    } else {
+      (i + 1) % polygon.len()
+    };
+    closest.replace((x, candidate));
+  }
+  // Holes outside of the polygon can't be bridged.
+  let Some((x, mut p_pos)) = closest else {
+    return;
+  };
+  // If a reflex vertex of the polygon is inside the triangle between the
+  // hole's vertex, the hit and the candidate, it blocks the candidate, so
+  // the reflex vertex closest in angle to the ray is used instead.
+  let hit = Point::new(x, m.y);
+  let p = vertices[polygon[p_pos]];
+  let (a, b, c) = if p.y < m.y { (m, p, hit) } else { (m, hit, p) };
+  let mut best_tan = f32::INFINITY;
+  for i in 0..polygon.len() {
+    let prev = vertices[polygon[(i + polygon.len() - 1) % polygon.len()]];
+    let r = vertices[polygon[i]];
+    let next = vertices[polygon[(i + 1) % polygon.len()]];
+    if i == p_pos || r.x < m.x || cross(prev, r, next) >= 0.0 || !in_triangle(r, a, b, c) {
+      continue;
+    }
+    let tan = (r.y - m.y).abs() / (r.x - m.x);
+    if tan < best_tan {
+      best_tan = tan;
+      p_pos = i;
+    }
+  }
+  // Splice the hole in after the bridge vertex, returning to it after
+  // going around the hole.
+  let mut bridged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+  bridged.extend_from_slice(&polygon[..=p_pos]);
+  bridged.extend(hole[m_pos..].iter().chain(&hole[..=m_pos]));
+  bridged.extend_from_slice(&polygon[p_pos..]);
+  *polygon = bridged;
+}
+
+/// Triangulate a counterclockwise polygon by clipping its ears.
+fn clip_ears(vertices: &[Point], mut polygon: Vec<usize>) -> Vec<u32> {
+  let mut indices = Vec::with_capacity(polygon.len().saturating_sub(2) * 3);
+  // How strictly ears are checked. Relaxed when no ear is found, which only
+  // happens if the polygon isn't simple.
+  // 0: Convex vertices whose triangle contains no other vertex.
+  // 1: Convex vertices.
+  // 2: Any vertex.
+  let mut strictness = 0;
+  let mut stalled = 0;
+  let mut i = 0;
+  while polygon.len() > 3 {
+    let len = polygon.len();
+    i %= len;
+    let (a, b, c) = (
+      polygon[(i + len - 1) % len],
+      polygon[i],
+      polygon[(i + 1) % len],
+    );
+    let (pa, pb, pc) = (vertices[a], vertices[b], vertices[c]);
+    let turn = cross(pa, pb, pc);
+    // Drop collinear vertices, since they don't form a triangle.
+    let is_collinear = turn.abs()
+      <= f32::EPSILON * (pb.x - pa.x).hypot(pb.y - pa.y) * (pc.x - pb.x).hypot(pc.y - pb.y);
+    let is_ear = match strictness {
+      0 => {
+        turn > 0.0
+          && !polygon.iter().any(|&j| {
+            let point = vertices[j];
+            point != pa && point != pb && point != pc && in_triangle(point, pa, pb, pc)
+          })
+      },
+      1 => turn > 0.0,
+      _ => true,
+    };
+    if is_collinear || is_ear {
+      if !is_collinear {
+        indices.extend([a as u32, b as u32, c as u32]);
+      }
+      polygon.remove(i);
+      strictness = 0;
+      stalled = 0;
+      continue;
+    }
+    // Relax the check once every vertex has been tried.
+    i += 1;
+    stalled += 1;
+    if stalled >= len {
+      strictness += 1;
+      stalled = 0;
+    }
+  }
+  // Add the last triangle.
+  if let [a, b, c] = polygon[..] {
+    if cross(vertices[a], vertices[b], vertices[c]) != 0.0 {
+      indices.extend([a as u32, b as u32, c as u32]);
+    }
+  }
+  indices
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn points(coords: &[[f32; 2]]) -> Vec<Point> {
+    coords.iter().map(|&coords| Point::from(coords)).collect()
+  }
+
+  /// Get the triangles of the indices, checking that each is wound
+  /// counterclockwise and not degenerate.
+  fn triangles(vertices: &[Point], indices: &[u32]) -> Vec<[Point; 3]> {
+    assert_eq!(indices.len() % 3, 0);
+    indices
+      .chunks(3)
+      .map(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+        assert!(cross(a, b, c) > 0.0);
+        [a, b, c]
+      })
+      .collect()
+  }
+
+  /// Get the total area of the triangles.
+  fn area(triangles: &[[Point; 3]]) -> f32 {
+    triangles
+      .iter()
+      .map(|&[a, b, c]| cross(a, b, c) / 2.0)
+      .sum()
+  }
+
+  #[test]
+  fn concave_polygons_are_covered() {
+    // An L shape, in both winding orders.
+    let mut outline = points(&[
+      [0.0, 0.0],
+      [2.0, 0.0],
+      [2.0, 1.0],
+      [1.0, 1.0],
+      [1.0, 2.0],
+      [0.0, 2.0],
+    ]);
+    for _ in 0..2 {
+      let triangles = triangles(&outline, &triangulate(&outline, &[]));
+      assert_eq!(triangles.len(), outline.len() - 2);
+      assert_eq!(area(&triangles), 3.0);
+      outline.reverse();
+    }
+  }
+
+  #[test]
+  fn holes_are_left_uncovered() {
+    let vertices = points(&[
+      [0.0, 0.0],
+      [4.0, 0.0],
+      [4.0, 4.0],
+      [0.0, 4.0],
+      [1.0, 1.0],
+      [3.0, 1.0],
+      [3.0, 3.0],
+      [1.0, 3.0],
+    ]);
+    let triangles = triangles(&vertices, &triangulate(&vertices, &[4]));
+    assert_eq!(area(&triangles), 12.0);
+    // No triangle covers the center of the hole.
+    let center = Point::new(2.0, 2.0);
+    assert!(!triangles
+      .iter()
+      .any(|&[a, b, c]| in_triangle(center, a, b, c)));
+  }
+
+  #[test]
+  fn holes_outside_of_the_outline_are_ignored() {
+    let vertices = points(&[
+      [0.0, 0.0],
+      [1.0, 0.0],
+      [1.0, 1.0],
+      [0.0, 1.0],
+      [5.0, 5.0],
+      [6.0, 5.0],
+      [6.0, 6.0],
+    ]);
+    let triangles = triangles(&vertices, &triangulate(&vertices, &[4]));
+    assert_eq!(area(&triangles), 1.0);
+  }
+
+  #[test]
+  fn collinear_vertices_are_dropped() {
+    // A square with vertices in the middle of its edges.
+    let vertices = points(&[
+      [0.0, 0.0],
+      [1.0, 0.0],
+      [2.0, 0.0],
+      [2.0, 1.0],
+      [2.0, 2.0],
+      [1.0, 2.0],
+      [0.0, 2.0],
+      [0.0, 1.0],
+    ]);
+    let triangles = triangles(&vertices, &triangulate(&vertices, &[]));
+    assert_eq!(area(&triangles), 4.0);
+  }
+
+  #[test]
+  fn degenerate_polygons_have_no_triangles() {
+    assert!(triangulate(&[], &[]).is_empty());
+    let two = points(&[[0.0, 0.0], [1.0, 0.0]]);
+    assert!(triangulate(&two, &[]).is_empty());
+    let line = points(&[[0.0, 0.0], [1.0, 1.0], [2.0, 2.0], [3.0, 3.0]]);
+    assert!(triangulate(&line, &[]).is_empty());
+    // Out of range and too short holes are ignored.
+    let triangle = points(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [5.0, 5.0]]);
+    let triangles = triangles(&triangle, &triangulate(&triangle, &[3, 10]));
+    assert_eq!(area(&triangles), 0.5);
+  }
+}
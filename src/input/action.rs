@@ -0,0 +1,102 @@
+use crate::{InputError, Key, MouseButton};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A physical input that an action can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+  Key(Key),
+  Mouse(MouseButton),
+}
+
+impl From<Key> for Binding {
+  fn from(key: Key) -> Self {
+    Self::Key(key)
+  }
+}
+
+impl From<MouseButton> for Binding {
+  fn from(button: MouseButton) -> Self {
+    Self::Mouse(button)
+  }
+}
+
+/// The bindings of an axis.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct AxisBindings {
+  /// Bindings that move the axis towards -1.
+  pub negative: Vec<Binding>,
+  /// Bindings that move the axis towards 1.
+  pub positive: Vec<Binding>,
+}
+
+/// Maps named actions and axes to their bindings.
+// Ordered maps are used so that saved bindings have a stable order.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Bindings {
+  actions: BTreeMap<String, Vec<Binding>>,
+  axes: BTreeMap<String, AxisBindings>,
+}
+
+impl Bindings {
+  /// Create new empty bindings.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Bind an action, replacing its previous bindings.
+  pub fn bind_action(
+    &mut self,
+    action: impl ToString,
+    bindings: impl IntoIterator<Item = impl Into<Binding>>,
+  ) {
+    let bindings = bindings.into_iter().map(Into::into).collect();
+    self.actions.insert(action.to_string(), bindings);
+  }
+  /// Bind an axis, replacing its previous bindings.
+  pub fn bind_axis(
+    &mut self,
+    axis: impl ToString,
+    negative: impl IntoIterator<Item = impl Into<Binding>>,
+    positive: impl IntoIterator<Item = impl Into<Binding>>,
+  ) {
+    let axis_bindings = AxisBindings {
+      negative: negative.into_iter().map(Into::into).collect(),
+      positive: positive.into_iter().map(Into::into).collect(),
+    };
+    self.axes.insert(axis.to_string(), axis_bindings);
+  }
+  /// Unbind an action.
+  pub fn unbind_action(&mut self, action: &str) {
+    self.actions.remove(action);
+  }
+  /// Unbind an axis.
+  pub fn unbind_axis(&mut self, axis: &str) {
+    self.axes.remove(axis);
+  }
+  /// Get the bindings of an action.
+  pub fn action(&self, action: &str) -> &[Binding] {
+    self.actions.get(action).map_or(&[], Vec::as_slice)
+  }
+  /// Get the bindings of an axis.
+  pub fn axis(&self, axis: &str) -> Option<&AxisBindings> {
+    self.axes.get(axis)
+  }
+  /// Parse bindings from a RON string.
+  pub fn from_ron(source: &str) -> Result<Self, InputError> {
+    Ok(ron::from_str(source)?)
+  }
+  /// Write the bindings to a RON string.
+  pub fn to_ron(&self) -> Result<String, InputError> {
+    Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+  }
+  /// Load bindings from a RON file.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, InputError> {
+    Self::from_ron(&fs::read_to_string(path)?)
+  }
+  /// Save the bindings to a RON file.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputError> {
+    fs::write(path, self.to_ron()?)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+/// Input-related errors.
+#[derive(Error, Debug)]
+pub enum InputError {
+  #[error("{0}")]
+  Io(#[from] std::io::Error),
+  #[error("{0}")]
+  Deserialize(#[from] ron::error::SpannedError),
+  #[error("{0}")]
+  Serialize(#[from] ron::Error),
+  #[error("Replay is out of sync: frame recorded {0} fixed steps in was replayed {1} in")]
+  ReplayDesync(u64, u64),
+}
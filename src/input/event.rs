@@ -0,0 +1,72 @@
+use crate::{Point, Vector};
+use serde::{Deserialize, Serialize};
+use winit::{
+  event::{ElementState, MouseScrollDelta, WindowEvent},
+  keyboard::PhysicalKey,
+};
+
+/// A physical key on the keyboard.
+pub type Key = winit::keyboard::KeyCode;
+
+/// A mouse button.
+pub type MouseButton = winit::event::MouseButton;
+
+/// An input event.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum InputEvent {
+  /// A key was pressed.
+  KeyPressed(Key),
+  /// A key was released.
+  KeyReleased(Key),
+  /// A mouse button was pressed.
+  MousePressed(MouseButton),
+  /// A mouse button was released.
+  MouseReleased(MouseButton),
+  /// The cursor moved to a position, in physical pixels relative to the
+  /// top-left corner of the window.
+  CursorMoved(Point),
+  /// The cursor left the window.
+  CursorLeft,
+  /// The mouse wheel was scrolled. The delta is in lines, or in pixels if
+  /// the device reports pixel deltas (eg. a touchpad).
+  Scrolled(Vector),
+}
+
+impl InputEvent {
+  /// Convert a window event to an input event.
+  /// Returns `None` if the window event is not an input event.
+  pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+    match event {
+      WindowEvent::KeyboardInput { event, .. } => {
+        // Key repeats don't change the state of the key.
+        if event.repeat {
+          None?
+        }
+        let PhysicalKey::Code(key) = event.physical_key else {
+          None?
+        };
+        match event.state {
+          ElementState::Pressed => Some(Self::KeyPressed(key)),
+          ElementState::Released => Some(Self::KeyReleased(key)),
+        }
+      },
+      WindowEvent::MouseInput { state, button, .. } => match state {
+        ElementState::Pressed => Some(Self::MousePressed(*button)),
+        ElementState::Released => Some(Self::MouseReleased(*button)),
+      },
+      WindowEvent::CursorMoved { position, .. } => Some(Self::CursorMoved(Point::new(
+        position.x as f32,
+        position.y as f32,
+      ))),
+      WindowEvent::CursorLeft { .. } => Some(Self::CursorLeft),
+      WindowEvent::MouseWheel { delta, .. } => match delta {
+        MouseScrollDelta::LineDelta(x, y) => Some(Self::Scrolled(Vector::new(*x, *y))),
+        MouseScrollDelta::PixelDelta(position) => Some(Self::Scrolled(Vector::new(
+          position.x as f32,
+          position.y as f32,
+        ))),
+      },
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,5 @@
+pub mod action;
+pub mod error;
+pub mod event;
+pub mod record;
+pub mod state;
@@ -0,0 +1,72 @@
+use crate::{InputError, InputEvent};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fs, path::Path};
+
+/// A recorded frame.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct RecordedFrame {
+  /// The fixed step that the frame started on.
+  pub tick: u64,
+  /// The number of fixed steps taken during the frame.
+  pub steps: u32,
+  /// The input events received before the frame.
+  pub events: Vec<InputEvent>,
+}
+
+/// A recording of input events and the fixed steps they arrived on.
+///
+/// Replaying a recording reproduces the input events and the number of
+/// fixed steps of every frame, so a session is reproduced exactly as long
+/// as the scenes only depend on the input and the fixed steps (rather than
+/// the real time between frames). Recordings should be started and replayed
+/// from the application init event, so that the session starts from the
+/// same state.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Recording {
+  frames: VecDeque<RecordedFrame>,
+}
+
+impl Recording {
+  /// Create a new empty recording.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Push a frame to the end of the recording.
+  pub fn push_frame(&mut self, frame: RecordedFrame) {
+    self.frames.push_back(frame);
+  }
+  /// Pop a frame from the start of the recording.
+  pub fn pop_frame(&mut self) -> Option<RecordedFrame> {
+    self.frames.pop_front()
+  }
+  /// Get the recorded frames.
+  pub fn frames(&self) -> impl Iterator<Item = &RecordedFrame> {
+    self.frames.iter()
+  }
+  /// Get the number of recorded frames.
+  pub fn len(&self) -> usize {
+    self.frames.len()
+  }
+  /// Get whether the recording has no frames.
+  pub fn is_empty(&self) -> bool {
+    self.frames.is_empty()
+  }
+  /// Parse a recording from a RON string.
+  pub fn from_ron(source: &str) -> Result<Self, InputError> {
+    Ok(ron::from_str(source)?)
+  }
+  /// Write the recording to a RON string.
+  pub fn to_ron(&self) -> Result<String, InputError> {
+    Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+  }
+  /// Load a recording from a RON file.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, InputError> {
+    Self::from_ron(&fs::read_to_string(path)?)
+  }
+  /// Save the recording to a RON file.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputError> {
+    fs::write(path, self.to_ron()?)?;
+    Ok(())
+  }
+}
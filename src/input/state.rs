@@ -0,0 +1,458 @@
+use crate::{
+  Binding, Bindings, Camera, EngineError, GlobalTransform, InputError, InputEvent, Key,
+  MouseButton, Point, RecordedFrame, Recording, Timer, Transform, Vector, World,
+};
+use rustc_hash::FxHashSet;
+use std::mem;
+
+/// The state of the keyboard and mouse.
+pub struct Input {
+  /// The keys that are held down.
+  keys: FxHashSet<Key>,
+  /// The keys that were pressed this frame.
+  keys_pressed: FxHashSet<Key>,
+  /// The keys that were released this frame.
+  keys_released: FxHashSet<Key>,
+  /// The mouse buttons that are held down.
+  buttons: FxHashSet<MouseButton>,
+  /// The mouse buttons that were pressed this frame.
+  buttons_pressed: FxHashSet<MouseButton>,
+  /// The mouse buttons that were released this frame.
+  buttons_released: FxHashSet<MouseButton>,
+  /// The position of the cursor. `None` if the cursor is not in the window.
+  cursor: Option<Point>,
+  /// The scroll delta accumulated this frame.
+  scroll: Vector,
+  /// The action and axis bindings.
+  bindings: Bindings,
+  /// The recording in progress, if any.
+  recording: Option<Recording>,
+  /// The events received this frame, if recording.
+  frame_events: Vec<InputEvent>,
+  /// The recording being replayed, if any.
+  replay: Option<Recording>,
+  /// The recorded fixed step and the timer's fixed step that the replay
+  /// started on, once its first frame is replayed.
+  replay_start: Option<(u64, u64)>,
+}
+
+impl Default for Input {
+  fn default() -> Self {
+    Self {
+      keys: FxHashSet::default(),
+      keys_pressed: FxHashSet::default(),
+      keys_released: FxHashSet::default(),
+      buttons: FxHashSet::default(),
+      buttons_pressed: FxHashSet::default(),
+      buttons_released: FxHashSet::default(),
+      cursor: None,
+      scroll: Vector::zeros(),
+      bindings: Bindings::new(),
+      recording: None,
+      frame_events: Vec::new(),
+      replay: None,
+      replay_start: None,
+    }
+  }
+}
+
+impl Input {
+  /// Create a new input state.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Handle an input event, updating the state. The event is recorded if
+  /// recording, and ignored if replaying.
+  pub fn handle(&mut self, event: InputEvent) {
+    if self.replay.is_some() {
+      return;
+    }
+    if self.recording.is_some() {
+      self.frame_events.push(event);
+    }
+    self.apply(event);
+  }
+  /// Update the state with an input event.
+  fn apply(&mut self, event: InputEvent) {
+    match event {
+      InputEvent::KeyPressed(key) => {
+        if self.keys.insert(key) {
+          self.keys_pressed.insert(key);
+        }
+      },
+      InputEvent::KeyReleased(key) => {
+        if self.keys.remove(&key) {
+          self.keys_released.insert(key);
+        }
+      },
+      InputEvent::MousePressed(button) => {
+        if self.buttons.insert(button) {
+          self.buttons_pressed.insert(button);
+        }
+      },
+      InputEvent::MouseReleased(button) => {
+        if self.buttons.remove(&button) {
+          self.buttons_released.insert(button);
+        }
+      },
+      InputEvent::CursorMoved(position) => {
+        self.cursor.replace(position);
+      },
+      InputEvent::CursorLeft => {
+        self.cursor.take();
+      },
+      InputEvent::Scrolled(delta) => self.scroll += delta,
+    }
+  }
+  /// Release all keys and mouse buttons. This is done when the window
+  /// loses focus, since the release events would otherwise be missed.
+  pub fn release_all(&mut self) {
+    // Release through `Input::handle` so that the releases are recorded.
+    let keys = self.keys.iter().copied().collect::<Vec<_>>();
+    for key in keys {
+      self.handle(InputEvent::KeyReleased(key));
+    }
+    let buttons = self.buttons.iter().copied().collect::<Vec<_>>();
+    for button in buttons {
+      self.handle(InputEvent::MouseReleased(button));
+    }
+  }
+  /// End the frame, clearing the per-frame state.
+  pub fn end_frame(&mut self) {
+    self.keys_pressed.clear();
+    self.keys_released.clear();
+    self.buttons_pressed.clear();
+    self.buttons_released.clear();
+    self.scroll = Vector::zeros();
+  }
+  /// Get whether a key is held down.
+  pub fn is_key_pressed(&self, key: Key) -> bool {
+    self.keys.contains(&key)
+  }
+  /// Get whether a key was pressed this frame.
+  pub fn is_key_just_pressed(&self, key: Key) -> bool {
+    self.keys_pressed.contains(&key)
+  }
+  /// Get whether a key was released this frame.
+  pub fn is_key_just_released(&self, key: Key) -> bool {
+    self.keys_released.contains(&key)
+  }
+  /// Get whether a mouse button is held down.
+  pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+    self.buttons.contains(&button)
+  }
+  /// Get whether a mouse button was pressed this frame.
+  pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+    self.buttons_pressed.contains(&button)
+  }
+  /// Get whether a mouse button was released this frame.
+  pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+    self.buttons_released.contains(&button)
+  }
+  /// Get the position of the cursor, in physical pixels relative to the
+  /// top-left corner of the window. `None` if the cursor is not in the
+  /// window.
+  pub fn cursor(&self) -> Option<Point> {
+    self.cursor
+  }
+  /// Get the position of the cursor in world space, using the projection
+  /// of the active camera. `fbd` is the frame buffer dimensions.
+  /// `None` if the cursor is not in the window.
+  pub fn cursor_world(
+    &self,
+    world: &mut World,
+    fbd: (u32, u32),
+  ) -> Result<Option<Point>, EngineError> {
+    let Some(cursor) = self.cursor else {
+      return Ok(None);
+    };
+    let active_camera = world.actives.camera()?;
+    let (transform, global_transform, camera) =
+      world.standard_inspect::<(&Transform, Option<&GlobalTransform>, &Camera)>(active_camera)?;
+    let position = global_transform.map_or(transform.position, |global| global.position);
+    Ok(Some(camera.screen_to_world(fbd, position, cursor)))
+  }
+  /// Get the scroll delta accumulated this frame.
+  pub fn scroll(&self) -> Vector {
+    self.scroll
+  }
+  /// Start recording, discarding the recording in progress, if any. The
+  /// keys and mouse buttons held down and the cursor position are recorded
+  /// as events, so a replay starts from the same input state.
+  pub fn start_recording(&mut self) {
+    self.recording.replace(Recording::new());
+    self.frame_events.clear();
+    let keys = self.keys.iter().map(|&key| InputEvent::KeyPressed(key));
+    let buttons = self
+      .buttons
+      .iter()
+      .map(|&button| InputEvent::MousePressed(button));
+    let cursor = self.cursor.map(InputEvent::CursorMoved);
+    self.frame_events.extend(keys.chain(buttons).chain(cursor));
+  }
+  /// Stop recording, returning the recording.
+  pub fn stop_recording(&mut self) -> Option<Recording> {
+    self.frame_events.clear();
+    self.recording.take()
+  }
+  /// Get whether a recording is in progress.
+  pub fn is_recording(&self) -> bool {
+    self.recording.is_some()
+  }
+  /// Record the frame, if recording. Invoked at the end of the frame, after
+  /// the fixed steps have been taken, even if the frame failed.
+  pub fn record_frame(&mut self, timer: &Timer) {
+    if let Some(recording) = &mut self.recording {
+      recording.push_frame(RecordedFrame {
+        tick: timer.steps() - timer.frame_steps() as u64,
+        steps: timer.frame_steps(),
+        events: mem::take(&mut self.frame_events),
+      });
+    }
+  }
+  /// Start replaying a recording, releasing everything first so the
+  /// replay starts from the recorded input state. Events received from the
+  /// window are ignored until the replay is finished.
+  pub fn start_replay(&mut self, recording: Recording) {
+    self.keys.clear();
+    self.buttons.clear();
+    self.cursor = None;
+    self.end_frame();
+    self.frame_events.clear();
+    self.replay_start = None;
+    if !recording.is_empty() {
+      self.replay.replace(recording);
+    }
+  }
+  /// Stop replaying, returning the frames that have not been replayed.
+  pub fn stop_replay(&mut self) -> Option<Recording> {
+    self.replay_start = None;
+    self.replay.take()
+  }
+  /// Get whether a recording is being replayed.
+  pub fn is_replaying(&self) -> bool {
+    self.replay.is_some()
+  }
+  /// Replay the next recorded frame, if replaying. The recorded events are
+  /// applied and the timer is forced to take the recorded number of fixed
+  /// steps. Invoked at the start of the frame, after the timer is ticked.
+  /// The replay is stopped if the fixed steps taken so far don't match the
+  /// recording, such as when a replayed frame failed differently.
+  pub fn replay_frame(&mut self, timer: &mut Timer) -> Result<(), InputError> {
+    let Some(replay) = &mut self.replay else {
+      return Ok(());
+    };
+    let Some(frame) = replay.pop_frame() else {
+      return Ok(());
+    };
+    let is_last = replay.is_empty();
+    // Check that the frame starts on the recorded fixed step, relative to
+    // the start of the replay.
+    let (recorded_start, start) = *self.replay_start.get_or_insert((frame.tick, timer.steps()));
+    let expected = frame.tick.saturating_sub(recorded_start);
+    let actual = timer.steps() - start;
+    if expected != actual {
+      self.stop_replay();
+      Err(InputError::ReplayDesync(expected, actual))?;
+    }
+    // Stop replaying once the last frame is popped.
+    if is_last {
+      self.stop_replay();
+    }
+    for event in frame.events {
+      self.apply(event);
+    }
+    timer.force_steps(frame.steps);
+    Ok(())
+  }
+  /// Get the action and axis bindings.
+  pub fn bindings(&self) -> &Bindings {
+    &self.bindings
+  }
+  /// Get the action and axis bindings mutably.
+  pub fn bindings_mut(&mut self) -> &mut Bindings {
+    &mut self.bindings
+  }
+  /// Set the action and axis bindings.
+  pub fn set_bindings(&mut self, bindings: Bindings) {
+    self.bindings = bindings;
+  }
+  /// Get whether a binding is held down.
+  pub fn is_binding_pressed(&self, binding: Binding) -> bool {
+    match binding {
+      Binding::Key(key) => self.is_key_pressed(key),
+      Binding::Mouse(button) => self.is_mouse_pressed(button),
+    }
+  }
+  /// Get whether a binding was pressed this frame.
+  pub fn is_binding_just_pressed(&self, binding: Binding) -> bool {
+    match binding {
+      Binding::Key(key) => self.is_key_just_pressed(key),
+      Binding::Mouse(button) => self.is_mouse_just_pressed(button),
+    }
+  }
+  /// Get whether a binding was released this frame.
+  pub fn is_binding_just_released(&self, binding: Binding) -> bool {
+    match binding {
+      Binding::Key(key) => self.is_key_just_released(key),
+      Binding::Mouse(button) => self.is_mouse_just_released(button),
+    }
+  }
+  /// Get whether a binding was held down before this frame.
+  fn was_binding_pressed(&self, binding: Binding) -> bool {
+    (self.is_binding_pressed(binding) || self.is_binding_just_released(binding))
+      && !self.is_binding_just_pressed(binding)
+  }
+  /// Get whether any of an action's bindings are held down.
+  pub fn is_action_pressed(&self, action: &str) -> bool {
+    let bindings = self.bindings.action(action);
+    bindings
+      .iter()
+      .any(|&binding| self.is_binding_pressed(binding))
+  }
+  /// Get whether an action's bindings were pressed this frame, while none
+  /// of them were held down before. Pressing a second binding while another
+  /// is held doesn't press the action again.
+  pub fn is_action_just_pressed(&self, action: &str) -> bool {
+    let bindings = self.bindings.action(action);
+    bindings
+      .iter()
+      .any(|&binding| self.is_binding_just_pressed(binding))
+      && !bindings
+        .iter()
+        .any(|&binding| self.was_binding_pressed(binding))
+  }
+  /// Get whether an action's bindings were released this frame, leaving
+  /// none of them held down.
+  pub fn is_action_just_released(&self, action: &str) -> bool {
+    let bindings = self.bindings.action(action);
+    bindings
+      .iter()
+      .any(|&binding| self.is_binding_just_released(binding))
+      && !self.is_action_pressed(action)
+  }
+  /// Get the value of an axis, ranging from -1 to 1.
+  /// Zero if the axis is not bound.
+  pub fn axis(&self, axis: &str) -> f32 {
+    let Some(axis_bindings) = self.bindings.axis(axis) else {
+      return 0.0;
+    };
+    let is_any_pressed = |bindings: &[Binding]| {
+      bindings
+        .iter()
+        .any(|&binding| self.is_binding_pressed(binding))
+    };
+    let negative = is_any_pressed(&axis_bindings.negative) as i8 as f32;
+    let positive = is_any_pressed(&axis_bindings.positive) as i8 as f32;
+    positive - negative
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Create an input state with a "jump" action bound to two keys.
+  fn setup() -> Input {
+    let mut input = Input::new();
+    input
+      .bindings_mut()
+      .bind_action("jump", [Key::Space, Key::KeyW]);
+    input
+  }
+
+  #[test]
+  fn action_is_just_pressed_once_per_hold() {
+    let mut input = setup();
+    input.handle(InputEvent::KeyPressed(Key::Space));
+    assert!(input.is_action_just_pressed("jump"));
+    input.end_frame();
+    assert!(!input.is_action_just_pressed("jump"));
+    // Pressing the second binding while the first is held doesn't press the
+    // action again.
+    input.handle(InputEvent::KeyPressed(Key::KeyW));
+    assert!(input.is_action_pressed("jump"));
+    assert!(!input.is_action_just_pressed("jump"));
+  }
+
+  #[test]
+  fn action_is_just_released_once_every_binding_is() {
+    let mut input = setup();
+    input.handle(InputEvent::KeyPressed(Key::Space));
+    input.handle(InputEvent::KeyPressed(Key::KeyW));
+    input.end_frame();
+    input.handle(InputEvent::KeyReleased(Key::Space));
+    assert!(!input.is_action_just_released("jump"));
+    input.end_frame();
+    input.handle(InputEvent::KeyReleased(Key::KeyW));
+    assert!(input.is_action_just_released("jump"));
+    assert!(!input.is_action_pressed("jump"));
+  }
+
+  #[test]
+  fn switching_bindings_within_a_frame_is_not_a_press() {
+    let mut input = setup();
+    input.handle(InputEvent::KeyPressed(Key::Space));
+    input.end_frame();
+    input.handle(InputEvent::KeyReleased(Key::Space));
+    input.handle(InputEvent::KeyPressed(Key::KeyW));
+    assert!(!input.is_action_just_pressed("jump"));
+    assert!(!input.is_action_just_released("jump"));
+  }
+
+  #[test]
+  fn unbound_actions_and_axes_are_idle() {
+    let mut input = setup();
+    input.handle(InputEvent::KeyPressed(Key::Space));
+    assert!(!input.is_action_pressed("fire"));
+    assert!(!input.is_action_just_pressed("fire"));
+    assert_eq!(input.axis("move_x"), 0.0);
+  }
+
+  #[test]
+  fn opposite_axis_bindings_cancel_out() {
+    let mut input = setup();
+    input
+      .bindings_mut()
+      .bind_axis("move_x", [Key::KeyA], [Key::KeyD]);
+    input.handle(InputEvent::KeyPressed(Key::KeyD));
+    assert_eq!(input.axis("move_x"), 1.0);
+    input.handle(InputEvent::KeyPressed(Key::KeyA));
+    assert_eq!(input.axis("move_x"), 0.0);
+  }
+
+  #[test]
+  fn replay_starts_released_and_stops_when_out_of_sync() {
+    let mut input = setup();
+    input.handle(InputEvent::KeyPressed(Key::Space));
+    let mut recording = Recording::new();
+    for tick in [10, 15] {
+      recording.push_frame(RecordedFrame {
+        tick: tick,
+        steps: 1,
+        events: Vec::new(),
+      });
+    }
+    input.start_replay(recording);
+    assert!(!input.is_action_pressed("jump"));
+    // The first frame starts the replay, and takes its recorded step.
+    let mut timer = Timer::new();
+    timer.tick_fixed();
+    input.replay_frame(&mut timer).unwrap();
+    while timer.step() {}
+    // The second frame was recorded 5 steps in, but is replayed 1 step in.
+    timer.tick_fixed();
+    assert!(matches!(
+      input.replay_frame(&mut timer),
+      Err(InputError::ReplayDesync(5, 1))
+    ));
+    assert!(!input.is_replaying());
+  }
+
+  #[test]
+  fn bindings_round_trip_through_ron() {
+    let input = setup();
+    let bindings = Bindings::from_ron(&input.bindings().to_ron().unwrap()).unwrap();
+    assert_eq!(bindings.action("jump"), input.bindings().action("jump"));
+  }
+}
@@ -4,28 +4,51 @@
 mod app;
 mod cmd;
 mod ctx;
+mod data;
 mod ecs;
 mod error;
 mod gfx;
+mod input;
 mod math;
 mod misc;
 mod phys;
+mod preload;
 mod scene;
+mod timer;
+mod transition;
 
 /* Exports. */
 pub use app::{
-  error::AppError,
+  error::{AppError, ErrorPolicy},
   handlers::{App, AppEventHandler, AppSetupHandler, AppWindowEventHandler},
+  headless::Headless,
 };
 pub use cmd::{
-  command::{commands::LoadScene, Command},
+  command::{
+    commands::{
+      LoadScene, LoadSceneWith, PauseSimulation, PopScene, PreloadScene, PushScene, PushSceneWith,
+      RebindAction, RebindAxis, ResumeSimulation, SetDebugDraw, SetFixedTimestep, SetTimeScale,
+      StartRecording, StartReplay, StepSimulation, StopRecording, ToggleDebugDraw, TransitionTo,
+    },
+    Command,
+  },
   queue::CommandQueue,
 };
 pub use ctx::Context;
+pub use data::{
+  document::{EntityDocument, EnvironmentCollider, SceneDocument},
+  error::DataError,
+  prefab::{Prefab, PrefabComponent, PrefabOverrides, Prefabs},
+  registry::{ComponentRegistry, StoredComponent},
+};
 pub use ecs::{
   actives::Actives,
+  commands::WorldCommands,
   component::{
-    components::{Camera, Collider, Renderable, RigidBody, Transform},
+    components::{
+      Camera, Children, Collider, GlobalTransform, Owner, Parent, Persistent, Renderable,
+      RigidBody, Text, TextAlign, TextSection, Transform,
+    },
     Component,
   },
   entity::Entity,
@@ -34,16 +57,29 @@ pub use ecs::{
 };
 pub use error::EngineError;
 pub use gfx::{
+  batch::{DynamicBatch, StaticBatch},
   color::Color,
+  debug::DebugDraw,
   error::GfxError,
+  font::{Font, Glyph, PositionedGlyph},
   mesh::Mesh,
+  overlay::{Compositor, Overlay},
   pipeline::{Pipeline, PipelineAttributes},
   program::Programs,
   renderer::Renderer,
   request::RenderRequest,
-  texture::{Texture, TextureInfo, Textures},
+  shape::LineJoin,
+  texture::{Texture, TextureInfo, Textures, BLANK_SAMPLER_ID},
+  triangulate::triangulate,
   vertex::Vertex,
 };
+pub use input::{
+  action::{AxisBindings, Binding, Bindings},
+  error::InputError,
+  event::{InputEvent, Key, MouseButton},
+  record::{RecordedFrame, Recording},
+  state::Input,
+};
 pub use math::{Matrix4, Point, Ray, Scale, Size, Vector, AABB};
 pub use misc::{flag::Flag, hash::TypeIdHasher};
 pub use phys::{
@@ -51,7 +87,14 @@ pub use phys::{
   simulator::Simulator,
   tree::{CollisionEvent, CollisionTree, TreeObject, TreeObjectSource},
 };
-pub use scene::{Scene, SceneError, Scenes};
+pub use preload::{
+  ActivePreload, AssetManifest, Assets, PreloadError, PreloadProgress, PreloadStep,
+};
+pub use scene::{Payload, Scene, SceneError, Scenes};
+pub use timer::{Timer, TimerError};
+pub use transition::{
+  ActiveTransition, Transition, TransitionDirection, TransitionPhase, TransitionStep,
+};
 
 /* Re-exports. */
 pub use winit::window::WindowBuilder;
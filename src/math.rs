@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Div, Mul, Sub};
 
 /// A point in 2-D space.
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Point {
   pub x: f32,
   pub y: f32,
@@ -16,6 +17,12 @@ impl Point {
   pub fn has_nan(&self) -> bool {
     self.x.is_nan() || self.y.is_nan()
   }
+  /// Rotate the point around the origin, by an angle in radians, from the
+  /// x axis towards the y axis.
+  pub fn rotate(self, angle: f32) -> Self {
+    let (sin, cos) = angle.sin_cos();
+    Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+  }
 }
 
 impl From<Point> for [f32; 2] {
@@ -118,7 +125,7 @@ pub type Vector = nalgebra::Vector2<f32>;
 pub type Matrix4 = nalgebra::Matrix4<f32>;
 
 /// A size.
-#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Size {
   pub w: f32,
   pub h: f32,
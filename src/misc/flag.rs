@@ -1,4 +1,5 @@
 /// A flag used to check for state change.
+#[derive(Clone)]
 pub struct Flag<T> {
   value: T,
   is_dirty: bool,
@@ -1,12 +1,26 @@
 use crate::{
-  dynrect_vs_rect, Collider, CollisionEvent, CollisionTree, Point, Renderer, RigidBody, Size,
-  Transform, TreeObjectSource, World,
+  dynrect_vs_rect, Collider, CollisionEvent, CollisionTree, Color, DebugDraw, GlobalTransform,
+  Point, RigidBody, Size, Transform, TreeObjectSource, Vector, World,
 };
+use rustc_hash::FxHashMap;
+use std::any::TypeId;
+
+/// The time that velocity arrows are drawn over, in seconds.
+const DEBUG_VELOCITY_SCALE: f32 = 0.25;
+
+/// The length of contact normal arrows.
+const DEBUG_NORMAL_LENGTH: f32 = 16.0;
 
 /// Simulates physics.
 #[derive(Default)]
 pub struct Simulator {
   tree: CollisionTree,
+  /// The scene that owns the environment colliders added, if any.
+  owner: Option<TypeId>,
+  /// The scenes that own environment colliders, by collider id.
+  owners: FxHashMap<u64, TypeId>,
+  /// The contact points and normals of the collisions of the last step.
+  contacts: Vec<(Point, Vector)>,
 }
 
 impl Simulator {
@@ -15,49 +29,102 @@ impl Simulator {
     Self::default()
   }
   /// Add an environment collider to the simulator.
+  /// If a scene owner is set, the collider is owned by that scene.
   pub fn add_environment_collider(
     &mut self,
     position: impl Into<Point>,
     size: impl Into<Size>,
   ) -> u64 {
-    self
+    let id = self
       .tree
-      .add_collider(position.into(), size.into(), TreeObjectSource::Environment)
+      .add_collider(position.into(), size.into(), TreeObjectSource::Environment);
+    if let Some(scene) = self.owner {
+      self.owners.insert(id, scene);
+    }
+    id
   }
-  /// Execute the simulator.
-  pub fn execute(
-    &mut self,
-    world: &mut World,
-    renderer: &mut Renderer,
-    timestep: f32,
-  ) -> Vec<CollisionEvent> {
+  /// Remove an environment collider from the simulator.
+  pub fn remove_environment_collider(&mut self, id: u64) {
+    self.tree.remove_collider(id);
+    self.owners.remove(&id);
+  }
+  /// Get the position and size of the environment colliders, in the order
+  /// they were added.
+  pub fn environment_colliders(&self) -> impl Iterator<Item = (Point, Size)> + '_ {
+    let mut colliders = self
+      .tree
+      .colliders()
+      .filter(|(_, object)| object.source == TreeObjectSource::Environment)
+      .collect::<Vec<_>>();
+    colliders.sort_by_key(|(id, _)| *id);
+    colliders
+      .into_iter()
+      .map(|(_, object)| (object.position, object.size))
+  }
+  /// Set the scene that owns the environment colliders added from now on,
+  /// if any.
+  pub fn set_owner(&mut self, owner: Option<TypeId>) {
+    self.owner = owner;
+  }
+  /// Remove the environment colliders owned by a scene.
+  pub fn remove_owned(&mut self, scene: TypeId) {
+    let owned = self
+      .owners
+      .iter()
+      .filter(|(_, owner)| **owner == scene)
+      .map(|(id, _)| *id)
+      .collect::<Vec<_>>();
+    for id in owned {
+      self.remove_environment_collider(id);
+    }
+  }
+  /// Execute the simulator. Collisions are resolved at the global positions
+  /// of the entities, and the movement is applied to their transforms.
+  /// Rotated entities collide as the bounding boxes of their rotated
+  /// colliders.
+  pub fn execute(&mut self, world: &mut World, timestep: f32) -> Vec<CollisionEvent> {
     // Collision events.
     // At the moment, this is just collision events between entities and colliders in the tree.
     let mut collision_events = Vec::with_capacity(0);
     // Query.
-    let query = world.standard_query::<(&mut Transform, &mut RigidBody, &Collider)>();
+    let query = world.standard_query::<(
+      &mut Transform,
+      Option<&mut GlobalTransform>,
+      &mut RigidBody,
+      &Collider,
+    )>();
+    // Forget the contacts of the last step.
+    self.contacts.clear();
     // Colliders inserted temporarily.
     let mut collider_insertions = Vec::new();
-    for (entity, (transform, rigid_body, collider)) in query {
+    for (entity, (transform, mut global_transform, rigid_body, collider)) in query {
+      // Get the bounds of the collider at the global transform, which is the
+      // transform for root entities.
+      let (position, size) = match global_transform.as_deref() {
+        Some(global_transform) => collider.bounds(global_transform),
+        None => collider.bounds(&GlobalTransform::from(&*transform)),
+      };
       // Broad phase against tree.
-      let broad_phase = self.tree.broad_phase(
-        transform.position + collider.offset,
-        collider.size,
-        rigid_body.velocity,
-        timestep,
-      );
+      let broad_phase = self
+        .tree
+        .broad_phase(position, size, rigid_body.velocity, timestep);
       // Create the source.
       let source = TreeObjectSource::Entity { handle: entity };
       // Narrow phase against statics returned by the broad phase.
       for static_object in broad_phase {
         if let Some(collision) = dynrect_vs_rect(
-          transform.position + collider.offset,
-          collider.size,
+          position,
+          size,
           rigid_body.velocity,
           static_object.position,
           static_object.size,
           timestep,
         ) {
+          // Keep track of the contact, at the center of the collider.
+          let contact = position
+            + rigid_body.velocity * timestep * collision.contact_time
+            + Vector::new(size.w / 2.0, size.h / 2.0);
+          self.contacts.push((contact, collision.contact_normal));
           // Adjust the velocity.
           rigid_body.velocity += collision
             .contact_normal
@@ -74,24 +141,24 @@ impl Simulator {
       // Narrow phase against other dynamic colliders.
       // !TODO: Implement this.
 
-      // Set the new position with the corrected velocity.
-      transform.position = transform.position + rigid_body.velocity * timestep;
+      // Set the new position with the corrected velocity. The movement is in
+      // the world, so it's rotated into the parent's frame for children,
+      // whose global rotation is their own added to their parent's.
+      let movement = rigid_body.velocity * timestep;
+      let parent_rotation = global_transform.as_deref().map_or(0.0, |global_transform| {
+        global_transform.rotation - transform.rotation
+      });
+      let local_movement = Point::new(movement.x, movement.y).rotate(-parent_rotation);
+      transform.position = transform.position + local_movement;
+      if let Some(global_transform) = global_transform.as_mut() {
+        global_transform.position = global_transform.position + movement;
+      }
       // Add the entity to the collision tree temporarily.
-      let id = self
-        .tree
-        .add_collider(transform.position + collider.offset, collider.size, source);
+      let id = self.tree.add_collider(position + movement, size, source);
       collider_insertions.push(id);
       // Apply the acceleration to the velocity.
       rigid_body.velocity += rigid_body.acceleration * timestep;
     }
-    // If enabled, draw the corners of the colliders in the tree.
-    #[cfg(feature = "show_hitboxes")]
-    {
-      self.tree.draw_collider_corners(renderer);
-    }
-    // This is just to fix linting.
-    // The renderer is only used for the "show_hitboxes" feature.
-    let _ = renderer;
     // Remove the insertions.
     for id in collider_insertions {
       self.tree.remove_collider(id);
@@ -99,4 +166,87 @@ impl Simulator {
     // Return the collision events.
     collision_events
   }
+  /// Draw the colliders, the velocities of the rigid bodies and the contacts
+  /// of the last step, if debug drawing is enabled.
+  pub fn debug_draw(&self, world: &mut World, debug_draw: &mut DebugDraw) {
+    if !debug_draw.is_enabled() {
+      return;
+    }
+    // Draw the environment colliders.
+    for (position, size) in self.environment_colliders() {
+      debug_draw.rect(position, size, Color::blue());
+    }
+    // Draw the colliders of the entities, and the velocities of those that
+    // have rigid bodies.
+    let query = world.standard_query::<(
+      &Transform,
+      Option<&GlobalTransform>,
+      Option<&RigidBody>,
+      &Collider,
+    )>();
+    for (_, (transform, global_transform, rigid_body, collider)) in query {
+      let (position, size) = match global_transform {
+        Some(global_transform) => collider.bounds(global_transform),
+        None => collider.bounds(&GlobalTransform::from(transform)),
+      };
+      debug_draw.rect(position, size, Color::green());
+      if let Some(rigid_body) = rigid_body {
+        let center = position + Vector::new(size.w / 2.0, size.h / 2.0);
+        debug_draw.arrow(
+          center,
+          center + rigid_body.velocity * DEBUG_VELOCITY_SCALE,
+          Color::red(),
+        );
+      }
+    }
+    // Draw the contact normals.
+    for &(point, normal) in &self.contacts {
+      debug_draw.arrow(
+        point,
+        point + normal * DEBUG_NORMAL_LENGTH,
+        Color::rgb(1.0, 1.0, 0.0),
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Parent;
+  use std::f32::consts::FRAC_PI_2;
+
+  fn global_position(world: &mut World, entity: crate::Entity) -> Point {
+    world
+      .standard_inspect::<&GlobalTransform>(entity)
+      .unwrap()
+      .position
+  }
+
+  #[test]
+  fn rotated_children_move_in_the_world() {
+    let mut world = World::new();
+    let parent =
+      world.spawn_entity((Transform::new([100.0, 100.0], [1.0, 1.0]).with_rotation(FRAC_PI_2),));
+    let mut rigid_body = RigidBody::new([60.0, 0.0]);
+    rigid_body.acceleration = Vector::new(0.0, 0.0);
+    let child = world.spawn_entity((
+      Transform::new([10.0, 0.0], [1.0, 1.0]),
+      Parent::new(parent),
+      rigid_body,
+      Collider::new([0.0, 0.0], [1.0, 1.0]),
+    ));
+    world.propagate_transforms();
+    let before = global_position(&mut world, child);
+    Simulator::new().execute(&mut world, 1.0);
+    // The global transform moves with the body, and keeps doing so once it's
+    // propagated from the moved local transform.
+    let moved = global_position(&mut world, child) - before;
+    world.propagate_transforms();
+    let propagated = global_position(&mut world, child) - before;
+    for movement in [moved, propagated] {
+      assert!((movement.x - 60.0).abs() < 1e-3);
+      assert!(movement.y.abs() < 1e-3);
+    }
+  }
 }
@@ -2,10 +2,6 @@ use crate::{Entity, Point, Size, Vector, AABB};
 use rstar::{iterators::LocateInEnvelopeIntersecting, Envelope, RTree, RTreeObject};
 use rustc_hash::FxHashMap;
 
-// Imports used only by the "show_hitboxes" feature.
-#[cfg(feature = "show_hitboxes")]
-use crate::{Color, RenderRequest, Renderer};
-
 /// A tree of colliders.
 pub struct CollisionTree {
   inner: RTree<TreeObject>,
@@ -57,17 +53,9 @@ impl CollisionTree {
     }
     obj
   }
-  /// Draw collider corners.
-  #[cfg(feature = "show_hitboxes")]
-  pub fn draw_collider_corners(&self, renderer: &mut Renderer) {
-    for collider in self.colliders.values() {
-      let min = collider.position;
-      let max = min + collider.size;
-      renderer.add_render_request(RenderRequest::point(min, Color::blue()));
-      renderer.add_render_request(RenderRequest::point([max.x, min.y], Color::blue()));
-      renderer.add_render_request(RenderRequest::point(max, Color::blue()));
-      renderer.add_render_request(RenderRequest::point([min.x, max.y], Color::blue()));
-    }
+  /// Get the colliders in the tree, by id.
+  pub fn colliders(&self) -> impl Iterator<Item = (u64, &TreeObject)> {
+    self.colliders.iter().map(|(id, object)| (*id, object))
   }
   /// Broad phase.
   pub fn broad_phase(
@@ -76,7 +64,7 @@ impl CollisionTree {
     size: Size,
     velocity: Vector,
     timestep: f32,
-  ) -> LocateInEnvelopeIntersecting<TreeObject> {
+  ) -> LocateInEnvelopeIntersecting<'_, TreeObject> {
     // Calculate the start and end AABBs. Merge them to create the
     // broad phase search area.
     let start = AABB::from_corners(position, position + size);
@@ -112,7 +100,7 @@ pub enum TreeObjectSource {
 }
 
 /// A collision event.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct CollisionEvent {
   pub source1: TreeObjectSource,
   pub source2: TreeObjectSource,
@@ -0,0 +1,295 @@
+use crate::{GfxError, Textures};
+use ahash::AHashMap;
+use image::RgbaImage;
+use std::{
+  any::{Any, TypeId},
+  fs, io,
+  path::PathBuf,
+  sync::mpsc::{self, Receiver, Sender, TryRecvError},
+  thread,
+};
+use thiserror::Error;
+
+/// A task that produces a custom asset on the worker thread.
+type Task = Box<dyn FnOnce() -> Result<Box<dyn Any + Send>, PreloadError> + Send>;
+
+/// An asset to decode on the worker thread.
+enum AssetSource {
+  /// An image file, decoded into a sampler.
+  Sampler {
+    name: String,
+    path: PathBuf,
+    info: Vec<(String, Vec<[f32; 2]>)>,
+  },
+  /// A data file, read into bytes.
+  Data { name: String, path: PathBuf },
+  /// A custom task.
+  Task { name: String, task: Task },
+}
+
+/// An asset decoded on the worker thread.
+enum DecodedAsset {
+  /// A decoded image, whose sampler is created on the main thread.
+  Sampler {
+    name: String,
+    image: RgbaImage,
+    info: Vec<(String, Vec<[f32; 2]>)>,
+  },
+  /// The bytes of a data file.
+  Data { name: String, bytes: Vec<u8> },
+  /// The output of a custom task.
+  Value {
+    name: String,
+    value: Box<dyn Any + Send>,
+  },
+}
+
+/// The assets to preload for a scene.
+#[derive(Default)]
+pub struct AssetManifest {
+  sources: Vec<AssetSource>,
+}
+
+impl AssetManifest {
+  /// Create a new, empty asset manifest.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Add an image file to decode into a sampler. `info` is the same as in
+  /// `Renderer::add_sampler`. The sampler's id is found under `name` in
+  /// the preloaded assets.
+  pub fn sampler(
+    mut self,
+    name: impl ToString,
+    path: impl Into<PathBuf>,
+    info: impl IntoIterator<Item = (impl ToString, Vec<[f32; 2]>)>,
+  ) -> Self {
+    self.sources.push(AssetSource::Sampler {
+      name: name.to_string(),
+      path: path.into(),
+      info: info
+        .into_iter()
+        .map(|(texture, texture_coords)| (texture.to_string(), texture_coords))
+        .collect(),
+    });
+    self
+  }
+  /// Add a data file, such as level data, to read. Its bytes are found
+  /// under `name` in the preloaded assets.
+  pub fn data(mut self, name: impl ToString, path: impl Into<PathBuf>) -> Self {
+    self.sources.push(AssetSource::Data {
+      name: name.to_string(),
+      path: path.into(),
+    });
+    self
+  }
+  /// Add a custom task to run on the worker thread, such as parsing level
+  /// data. Its output is found under `name` in the preloaded assets.
+  pub fn task<T: Send + 'static>(
+    mut self,
+    name: impl ToString,
+    task: impl FnOnce() -> Result<T, PreloadError> + Send + 'static,
+  ) -> Self {
+    self.sources.push(AssetSource::Task {
+      name: name.to_string(),
+      task: Box::new(|| task().map(|value| Box::new(value) as Box<dyn Any + Send>)),
+    });
+    self
+  }
+  /// Get the number of assets in the manifest.
+  pub fn len(&self) -> usize {
+    self.sources.len()
+  }
+  /// Get whether the manifest is empty.
+  pub fn is_empty(&self) -> bool {
+    self.sources.is_empty()
+  }
+}
+
+/// The assets preloaded for a scene. Passed to `Scene::receive_assets`.
+#[derive(Default)]
+pub struct Assets {
+  samplers: AHashMap<String, u16>,
+  data: AHashMap<String, Vec<u8>>,
+  values: AHashMap<String, Box<dyn Any + Send>>,
+}
+
+impl Assets {
+  /// Get the id of a preloaded sampler.
+  pub fn sampler(&self, name: &str) -> Option<u16> {
+    self.samplers.get(name).copied()
+  }
+  /// Get the bytes of a preloaded data file.
+  pub fn data(&self, name: &str) -> Option<&[u8]> {
+    self.data.get(name).map(Vec::as_slice)
+  }
+  /// Take the bytes of a preloaded data file.
+  pub fn take_data(&mut self, name: &str) -> Option<Vec<u8>> {
+    self.data.remove(name)
+  }
+  /// Take the output of a custom task.
+  /// Returns `None` if there is no output of type `T` under `name`.
+  pub fn take<T: 'static>(&mut self, name: &str) -> Option<T> {
+    if !self.values.get(name)?.is::<T>() {
+      return None;
+    }
+    let value = self.values.remove(name)?;
+    (value as Box<dyn Any>).downcast().ok().map(|value| *value)
+  }
+}
+
+/// The progress of a preload.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreloadProgress {
+  /// The number of assets that are ready.
+  pub loaded: usize,
+  /// The total number of assets.
+  pub total: usize,
+}
+
+impl PreloadProgress {
+  /// Get the progress as a fraction, from 0 to 1.
+  pub fn fraction(&self) -> f32 {
+    if self.total == 0 {
+      return 1.0;
+    }
+    self.loaded as f32 / self.total as f32
+  }
+}
+
+/// What to do after polling a preload.
+pub enum PreloadStep {
+  /// Assets were loaded since the last poll.
+  Progress(PreloadProgress),
+  /// Nothing changed since the last poll.
+  Pending,
+  /// Every asset is ready, so the scene can be switched to.
+  Finished(TypeId, Assets),
+}
+
+/// A preload in progress.
+pub struct ActivePreload {
+  /// The type id of the scene to switch to.
+  tid: TypeId,
+  /// Receives the decoded assets from the worker thread.
+  receiver: Receiver<Result<DecodedAsset, PreloadError>>,
+  /// The assets that are ready.
+  assets: Assets,
+  /// The progress of the preload.
+  progress: PreloadProgress,
+}
+
+impl ActivePreload {
+  /// Start decoding the assets of a manifest on a worker thread.
+  pub fn new(manifest: AssetManifest, tid: TypeId) -> Result<Self, PreloadError> {
+    let total = manifest.len();
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+      .name("stage-preload".to_string())
+      .spawn(move || decode(manifest, sender))?;
+    Ok(Self {
+      tid: tid,
+      receiver: receiver,
+      assets: Assets::default(),
+      progress: PreloadProgress {
+        loaded: 0,
+        total: total,
+      },
+    })
+  }
+  /// Get the type id of the scene to switch to.
+  pub fn tid(&self) -> TypeId {
+    self.tid
+  }
+  /// Get the progress of the preload.
+  pub fn progress(&self) -> PreloadProgress {
+    self.progress
+  }
+  /// Receive the assets decoded since the last poll without blocking,
+  /// finishing them on the main thread with `upload`, which creates the
+  /// sampler of a decoded image and returns its id.
+  pub fn poll(
+    mut self,
+    mut upload: impl FnMut(RgbaImage, Vec<(String, Vec<[f32; 2]>)>) -> Result<u16, GfxError>,
+  ) -> Result<(Option<Self>, PreloadStep), PreloadError> {
+    let loaded = self.progress.loaded;
+    // Finish the assets that are ready.
+    while self.progress.loaded < self.progress.total {
+      let decoded = match self.receiver.try_recv() {
+        Ok(decoded) => decoded?,
+        Err(TryRecvError::Empty) => break,
+        Err(TryRecvError::Disconnected) => Err(PreloadError::WorkerDisconnected)?,
+      };
+      match decoded {
+        DecodedAsset::Sampler { name, image, info } => {
+          let sampler_id = upload(image, info)?;
+          self.assets.samplers.insert(name, sampler_id);
+        },
+        DecodedAsset::Data { name, bytes } => {
+          self.assets.data.insert(name, bytes);
+        },
+        DecodedAsset::Value { name, value } => {
+          self.assets.values.insert(name, value);
+        },
+      }
+      self.progress.loaded += 1;
+    }
+    // Finish the preload once every asset is ready.
+    if self.progress.loaded == self.progress.total {
+      let tid = self.tid;
+      return Ok((None, PreloadStep::Finished(tid, self.assets)));
+    }
+    let step = if self.progress.loaded > loaded {
+      PreloadStep::Progress(self.progress)
+    } else {
+      PreloadStep::Pending
+    };
+    Ok((Some(self), step))
+  }
+}
+
+/// Decode the assets of a manifest, sending them to the main thread. Stops
+/// at the first error, or once the preload is dropped.
+fn decode(manifest: AssetManifest, sender: Sender<Result<DecodedAsset, PreloadError>>) {
+  for source in manifest.sources {
+    let decoded = match source {
+      AssetSource::Sampler { name, path, info } => fs::read(path)
+        .map_err(PreloadError::from)
+        .and_then(|bytes| Textures::decode_sampler(bytes).map_err(PreloadError::from))
+        .map(|image| DecodedAsset::Sampler {
+          name: name,
+          image: image,
+          info: info,
+        }),
+      AssetSource::Data { name, path } => {
+        fs::read(path)
+          .map_err(PreloadError::from)
+          .map(|bytes| DecodedAsset::Data {
+            name: name,
+            bytes: bytes,
+          })
+      },
+      AssetSource::Task { name, task } => task().map(|value| DecodedAsset::Value {
+        name: name,
+        value: value,
+      }),
+    };
+    let failed = decoded.is_err();
+    if sender.send(decoded).is_err() || failed {
+      return;
+    }
+  }
+}
+
+/// Preload-related errors.
+#[derive(Error, Debug)]
+pub enum PreloadError {
+  #[error("{0}")]
+  Io(#[from] io::Error),
+  #[error("{0}")]
+  Gfx(#[from] GfxError),
+  #[error("{0}")]
+  Task(String),
+  #[error("The preload worker stopped unexpectedly")]
+  WorkerDisconnected,
+}
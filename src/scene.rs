@@ -1,5 +1,13 @@
-use crate::{CollisionEvent, CommandQueue, Context, EngineError, TypeIdHasher};
-use std::{any::TypeId, collections::HashMap, hash::BuildHasherDefault};
+use crate::{
+  ActivePreload, ActiveTransition, AssetManifest, Assets, CollisionEvent, CommandQueue, Context,
+  EngineError, PreloadProgress, PreloadStep, Transition, TransitionPhase, TransitionStep,
+  TypeIdHasher,
+};
+use std::{
+  any::{Any, TypeId},
+  collections::HashMap,
+  hash::BuildHasherDefault,
+};
 use thiserror::Error;
 
 /// Defines a scene.
@@ -29,26 +37,122 @@ pub trait Scene: 'static {
     command_queue: &mut CommandQueue,
     context: &mut Context,
   ) -> Result<(), EngineError>;
+  /// Invoked when another scene is pushed on top of the scene.
+  fn pause(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Invoked when the scene on top of the scene is popped.
+  fn resume(
+    &mut self,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Invoked with the payload the scene is loaded with, before the previous
+  /// scenes are unloaded or paused and before `load`. If the payload is
+  /// rejected, the previous scenes stay loaded. By default, scenes don't
+  /// accept payloads.
+  fn receive(
+    &mut self,
+    _payload: Payload,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Err(SceneError::UnexpectedPayload)?
+  }
+  /// Invoked after `unload` to take the result of the scene, if any. The
+  /// result is passed to the scene that is loaded or resumed next.
+  fn result(&mut self) -> Option<Payload> {
+    None
+  }
+  /// Invoked with the result of the previous scene, after the scene is
+  /// loaded or resumed. By default, the result is ignored.
+  fn receive_result(
+    &mut self,
+    _result: Payload,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Invoked on the loaded scene with the progress of a scene being
+  /// preloaded, whenever assets are ready.
+  fn preload_progress(
+    &mut self,
+    _progress: PreloadProgress,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Invoked with the assets preloaded for the scene, before the previous
+  /// scenes are unloaded and before `load`. By default, the assets are
+  /// dropped, though their samplers stay added.
+  fn receive_assets(
+    &mut self,
+    _assets: Assets,
+    _command_queue: &mut CommandQueue,
+    _context: &mut Context,
+  ) -> Result<(), EngineError> {
+    Ok(())
+  }
+  /// Whether the scene's frame and postframe are still invoked while
+  /// another scene is on top of it. Note that the entities of a paused
+  /// scene are still simulated regardless.
+  fn update_while_paused(&self) -> bool {
+    false
+  }
+  /// Whether the entities the scene owns are still rendered while another
+  /// scene is on top of it.
+  fn render_while_paused(&self) -> bool {
+    true
+  }
+}
+
+/// Data passed to a scene when it is loaded, or returned from a scene when
+/// it is unloaded.
+pub struct Payload(Box<dyn Any>);
+
+impl Payload {
+  /// Create a new payload.
+  pub fn new<T: 'static>(data: T) -> Self {
+    Self(Box::new(data))
+  }
+  /// Get whether the payload is of type `T`.
+  pub fn is<T: 'static>(&self) -> bool {
+    self.0.is::<T>()
+  }
+  /// Take the data out of the payload.
+  pub fn downcast<T: 'static>(self) -> Result<T, SceneError> {
+    self
+      .0
+      .downcast()
+      .map(|data| *data)
+      .map_err(|_| SceneError::PayloadMismatch)
+  }
+  /// Get a reference to the data in the payload.
+  pub fn downcast_ref<T: 'static>(&self) -> Result<&T, SceneError> {
+    self.0.downcast_ref().ok_or(SceneError::PayloadMismatch)
+  }
 }
 
 /// Manages scenes.
+#[derive(Default)]
 pub struct Scenes {
-  /// The currently loaded scene.
-  loaded: TypeId,
+  /// The stack of loaded scenes. The last scene is the active scene, and
+  /// the scenes below it are paused.
+  stack: Vec<TypeId>,
   /// The registered scenes.
   scenes: HashMap<TypeId, Box<dyn Scene>, BuildHasherDefault<TypeIdHasher>>,
-}
-
-impl Default for Scenes {
-  fn default() -> Self {
-    // A dummy type id.
-    let dummy_loaded = TypeId::of::<()>();
-    // Return the scene manager.
-    Self {
-      loaded: dummy_loaded,
-      scenes: HashMap::default(),
-    }
-  }
+  /// The transition in progress, if any.
+  transition: Option<ActiveTransition>,
+  /// The preload in progress, if any.
+  preload: Option<ActivePreload>,
 }
 
 impl Scenes {
@@ -62,34 +166,376 @@ impl Scenes {
     self.scenes.insert(tid, Box::new(scene));
     self
   }
-  /// Get the loaded scene.
-  pub fn loaded(&mut self) -> Result<&mut dyn Scene, SceneError> {
+  /// Get a registered scene.
+  fn get(&mut self, tid: TypeId) -> Result<&mut dyn Scene, SceneError> {
     self
       .scenes
-      .get_mut(&self.loaded)
-      .map(|loaded| loaded.as_mut())
+      .get_mut(&tid)
+      .map(|scene| scene.as_mut())
       .ok_or(SceneError::SceneNotFound)
   }
-  /// Load a scene, unloading the previous scene.
+  /// Get the loaded scene, which is the scene on top of the stack.
+  pub fn loaded(&mut self) -> Result<&mut dyn Scene, SceneError> {
+    let tid = *self.stack.last().ok_or(SceneError::SceneNotFound)?;
+    self.get(tid)
+  }
+  /// Get the number of loaded scenes.
+  pub fn depth(&self) -> usize {
+    self.stack.len()
+  }
+  /// Load a scene, unloading the previous scenes.
   pub fn load<S: Scene>(
     &mut self,
     command_queue: &mut CommandQueue,
     context: &mut Context,
   ) -> Result<(), EngineError> {
-    // Get the previous scene, if there is one.
-    if let Ok(prev_scene) = self.loaded() {
-      // Unload the previous scene.
-      prev_scene.unload(command_queue, context)?;
+    self.switch(TypeId::of::<S>(), None, command_queue, context)
+  }
+  /// Load a scene with a payload, unloading the previous scenes.
+  pub fn load_with<S: Scene>(
+    &mut self,
+    payload: Payload,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    self.switch(TypeId::of::<S>(), Some(payload), command_queue, context)
+  }
+  /// Unload the loaded scenes and load a new one.
+  fn switch(
+    &mut self,
+    tid: TypeId,
+    payload: Option<Payload>,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // Get the new scene and pass it the payload first, so nothing is
+    // unloaded if it doesn't exist or rejects the payload.
+    self.deliver(tid, payload, command_queue, context)?;
+    // Unload the previous scenes, from the top of the stack down. The
+    // result of the loaded scene is kept.
+    let mut result = None;
+    while let Some(&prev_tid) = self.stack.last() {
+      let prev_result = self.unload(prev_tid, command_queue, context)?;
+      self.stack.pop();
+      result = result.or(prev_result);
     }
-    // Get the new scene.
+    // Load the new scene.
+    self.enter(tid, result, command_queue, context)
+  }
+  /// Transition to a scene, unloading the previous scenes once the old
+  /// scene is faded out or captured. The payload, if any, is passed to the
+  /// new scene when it is loaded. A transition replaces the transition in
+  /// progress, if any.
+  pub fn transition<S: Scene>(
+    &mut self,
+    transition: Transition,
+    payload: Option<Payload>,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // Get the new scene first, so the transition doesn't start if it
+    // doesn't exist.
+    let tid = TypeId::of::<S>();
+    self.get(tid)?;
+    // Start the transition.
+    let active_transition = ActiveTransition::new(transition, tid, payload);
+    if active_transition.phase() == TransitionPhase::Capture {
+      context.renderer.request_capture();
+    }
+    self.transition.replace(active_transition);
+    Ok(())
+  }
+  /// Get whether a transition is in progress.
+  pub fn is_transitioning(&self) -> bool {
+    self.transition.is_some()
+  }
+  /// Advance the transition in progress, if any. Invoked at the start of
+  /// every frame.
+  pub fn update_transition(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let Some(mut active_transition) = self.transition.take() else {
+      return Ok(());
+    };
+    // Transitions advance in real time, regardless of the time scale.
+    let delta = context.timer.delta().as_secs_f32();
+    match active_transition.advance(delta) {
+      TransitionStep::Overlay(overlay) => context.renderer.set_overlay(overlay),
+      TransitionStep::Switch(tid, payload, overlay) => {
+        // If switching fails, the transition is dropped, so the error
+        // isn't repeated every frame.
+        self.switch(tid, payload, command_queue, context)?;
+        context.renderer.set_overlay(overlay);
+      },
+      TransitionStep::Finished => {
+        context.renderer.clear_capture();
+        return Ok(());
+      },
+    }
+    self.transition.replace(active_transition);
+    Ok(())
+  }
+  /// Preload a scene's assets on a worker thread, then switch to the
+  /// scene, unloading the previous scenes. The preloaded `Assets` are
+  /// passed to the new scene's `receive_assets`. Meanwhile, the loaded scene
+  /// is updated as usual and receives the progress of the preload. A
+  /// preload replaces the preload in progress, if any.
+  pub fn preload<S: Scene>(&mut self, manifest: AssetManifest) -> Result<(), EngineError> {
+    // Get the new scene first, so the preload doesn't start if it doesn't
+    // exist.
     let tid = TypeId::of::<S>();
-    let new_scene = self.scenes.get_mut(&tid).ok_or(SceneError::SceneNotFound)?;
+    self.get(tid)?;
+    // Start the preload.
+    self.preload.replace(ActivePreload::new(manifest, tid)?);
+    Ok(())
+  }
+  /// Get whether a preload is in progress.
+  pub fn is_preloading(&self) -> bool {
+    self.preload.is_some()
+  }
+  /// Finish the assets of the preload in progress that are ready, if any.
+  /// Invoked at the start of every frame.
+  pub fn update_preload(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let Some(active_preload) = self.preload.take() else {
+      return Ok(());
+    };
+    // The GL uploads are done here, on the main thread. If they fail, the
+    // preload is dropped.
+    let total = active_preload.progress().total;
+    let renderer = &mut context.renderer;
+    let (active_preload, step) =
+      active_preload.poll(|image, info| renderer.add_decoded_sampler(image, info))?;
+    self.preload = active_preload;
+    match step {
+      PreloadStep::Pending => {},
+      PreloadStep::Progress(progress) => self.report_preload(progress, command_queue, context)?,
+      PreloadStep::Finished(tid, assets) => {
+        let progress = PreloadProgress {
+          loaded: total,
+          total: total,
+        };
+        self.report_preload(progress, command_queue, context)?;
+        // Pass the assets to the new scene first, so nothing is unloaded if
+        // it fails.
+        let scene = self.get(tid)?;
+        owned(tid, context, |context| {
+          scene.receive_assets(assets, command_queue, context)
+        })?;
+        self.switch(tid, None, command_queue, context)?;
+      },
+    }
+    Ok(())
+  }
+  /// Pass the progress of the preload to the loaded scene, if any.
+  fn report_preload(
+    &mut self,
+    progress: PreloadProgress,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let Some(&tid) = self.stack.last() else {
+      return Ok(());
+    };
+    let scene = self.get(tid)?;
+    owned(tid, context, |context| {
+      scene.preload_progress(progress, command_queue, context)
+    })
+  }
+  /// Push a scene on top of the loaded scene, pausing the loaded scene.
+  pub fn push<S: Scene>(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    self.overlay(TypeId::of::<S>(), None, command_queue, context)
+  }
+  /// Push a scene with a payload on top of the loaded scene, pausing the
+  /// loaded scene.
+  pub fn push_with<S: Scene>(
+    &mut self,
+    payload: Payload,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    self.overlay(TypeId::of::<S>(), Some(payload), command_queue, context)
+  }
+  /// Pause the loaded scene and load a new one on top of it.
+  fn overlay(
+    &mut self,
+    tid: TypeId,
+    payload: Option<Payload>,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // A scene can only be loaded once.
+    if self.stack.contains(&tid) {
+      Err(SceneError::SceneAlreadyLoaded)?;
+    }
+    // Pass the payload to the new scene first, so the loaded scene isn't
+    // paused if it's rejected.
+    self.deliver(tid, payload, command_queue, context)?;
+    // Pause the loaded scene, if there is one.
+    if let Some(&prev_tid) = self.stack.last() {
+      let prev_scene = self.get(prev_tid)?;
+      owned(prev_tid, context, |context| {
+        prev_scene.pause(command_queue, context)
+      })?;
+    }
     // Load the new scene.
-    new_scene.load(command_queue, context)?;
-    // Set the `loaded` field to the new scene's type id.
-    self.loaded = tid;
+    self.enter(tid, None, command_queue, context)
+  }
+  /// Pass a payload, if any, to a scene that is about to be loaded.
+  fn deliver(
+    &mut self,
+    tid: TypeId,
+    payload: Option<Payload>,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let scene = self.get(tid)?;
+    let Some(payload) = payload else {
+      return Ok(());
+    };
+    owned(tid, context, |context| {
+      scene.receive(payload, command_queue, context)
+    })
+  }
+  /// Load a scene onto the top of the stack, passing it the result of the
+  /// previous scene.
+  fn enter(
+    &mut self,
+    tid: TypeId,
+    result: Option<Payload>,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let scene = self.get(tid)?;
+    owned(tid, context, |context| {
+      // Load the new scene.
+      scene.load(command_queue, context)?;
+      // Pass the result of the previous scene to the new scene.
+      if let Some(result) = result {
+        scene.receive_result(result, command_queue, context)?;
+      }
+      Ok::<(), EngineError>(())
+    })?;
+    // Push the new scene's type id onto the stack.
+    self.stack.push(tid);
     Ok(())
   }
+  /// Pop the loaded scene, resuming the scene below it.
+  pub fn pop(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    // There must be a scene below to resume.
+    if self.stack.len() < 2 {
+      Err(SceneError::NoSceneBelow)?;
+    }
+    // Unload the loaded scene, taking its result.
+    let tid = *self.stack.last().ok_or(SceneError::SceneNotFound)?;
+    let result = self.unload(tid, command_queue, context)?;
+    self.stack.pop();
+    // Resume the scene below, passing it the result.
+    let tid = *self.stack.last().ok_or(SceneError::SceneNotFound)?;
+    let scene = self.get(tid)?;
+    owned(tid, context, |context| {
+      scene.resume(command_queue, context)?;
+      if let Some(result) = result {
+        scene.receive_result(result, command_queue, context)?;
+      }
+      Ok(())
+    })
+  }
+  /// Unload a scene on the stack, returning its result. The entities and
+  /// environment colliders the scene owns are removed, except for
+  /// persistent entities. The scene is only taken off the stack once it's
+  /// unloaded, so a scene that fails to unload stays loaded.
+  fn unload(
+    &mut self,
+    tid: TypeId,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<Option<Payload>, EngineError> {
+    let scene = self.get(tid)?;
+    owned(tid, context, |context| scene.unload(command_queue, context))?;
+    let result = scene.result();
+    context.world.despawn_owned(tid);
+    context.simulator.remove_owned(tid);
+    Ok(result)
+  }
+  /// Invoke the frame of the loaded scene, and of the paused scenes that
+  /// update while paused. Scenes are invoked from the bottom of the stack up.
+  pub fn frame(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+  ) -> Result<(), EngineError> {
+    let top = self
+      .stack
+      .len()
+      .checked_sub(1)
+      .ok_or(SceneError::SceneNotFound)?;
+    let mut hidden_scenes = Vec::new();
+    for (i, &tid) in self.stack.iter().enumerate() {
+      let scene = self.scenes.get_mut(&tid).ok_or(SceneError::SceneNotFound)?;
+      if i != top && !scene.render_while_paused() {
+        hidden_scenes.push(tid);
+      }
+      if i == top || scene.update_while_paused() {
+        owned(tid, context, |context| scene.frame(command_queue, context))?;
+      }
+    }
+    // Hide the entities of the paused scenes that aren't rendered.
+    context.renderer.set_hidden_scenes(hidden_scenes);
+    Ok(())
+  }
+  /// Invoke the postframe of the loaded scene, and of the paused scenes
+  /// that update while paused. Scenes are invoked from the bottom of the
+  /// stack up.
+  pub fn postframe(
+    &mut self,
+    command_queue: &mut CommandQueue,
+    context: &mut Context,
+    collision_events: Vec<CollisionEvent>,
+  ) -> Result<(), EngineError> {
+    let top = self
+      .stack
+      .len()
+      .checked_sub(1)
+      .ok_or(SceneError::SceneNotFound)?;
+    for &tid in &self.stack[..top] {
+      let scene = self.scenes.get_mut(&tid).ok_or(SceneError::SceneNotFound)?;
+      if scene.update_while_paused() {
+        let collision_events = collision_events.clone();
+        owned(tid, context, |context| {
+          scene.postframe(command_queue, context, collision_events)
+        })?;
+      }
+    }
+    let tid = self.stack[top];
+    let scene = self.get(tid)?;
+    owned(tid, context, |context| {
+      scene.postframe(command_queue, context, collision_events)
+    })
+  }
+}
+
+/// Invoke a scene callback, with the entities and environment colliders
+/// it adds owned by the scene. The deferred world changes it records are
+/// applied before returning, so the entities they spawn are owned too.
+fn owned<R>(tid: TypeId, context: &mut Context, callback: impl FnOnce(&mut Context) -> R) -> R {
+  context.set_owner(Some(tid));
+  let result = callback(context);
+  context.world_commands.apply(&mut context.world);
+  context.set_owner(None);
+  result
 }
 
 /// Scene-related errors.
@@ -97,4 +543,229 @@ impl Scenes {
 pub enum SceneError {
   #[error("Scene not found")]
   SceneNotFound,
+  #[error("Scene is already loaded")]
+  SceneAlreadyLoaded,
+  #[error("No scene below the loaded scene to resume")]
+  NoSceneBelow,
+  #[error("Scene does not accept a payload")]
+  UnexpectedPayload,
+  #[error("Payload is not of the expected type")]
+  PayloadMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Owner, Parent, Persistent, Point, Transform};
+
+  /// A scene that spawns an entity through the deferred world changes every
+  /// frame.
+  struct Spawner;
+
+  impl Scene for Spawner {
+    fn load(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn frame(&mut self, _: &mut CommandQueue, context: &mut Context) -> Result<(), EngineError> {
+      context
+        .world_commands
+        .spawn((Transform::new([0.0, 0.0], [1.0, 1.0]),));
+      Ok(())
+    }
+    fn postframe(
+      &mut self,
+      _: &mut CommandQueue,
+      _: &mut Context,
+      _: Vec<CollisionEvent>,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn unload(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  /// A scene that spawns an entity with a persistent child when loaded.
+  struct Carrier;
+
+  impl Scene for Carrier {
+    fn load(&mut self, _: &mut CommandQueue, context: &mut Context) -> Result<(), EngineError> {
+      let parent = context
+        .world
+        .spawn_entity((Transform::new([10.0, 0.0], [1.0, 1.0]),));
+      context.world.spawn_entity((
+        Transform::new([5.0, 0.0], [1.0, 1.0]),
+        Parent::new(parent),
+        Persistent,
+      ));
+      Ok(())
+    }
+    fn frame(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn postframe(
+      &mut self,
+      _: &mut CommandQueue,
+      _: &mut Context,
+      _: Vec<CollisionEvent>,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn unload(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  /// A scene that fails to unload.
+  struct Stubborn;
+
+  impl Scene for Stubborn {
+    fn load(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn frame(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn postframe(
+      &mut self,
+      _: &mut CommandQueue,
+      _: &mut Context,
+      _: Vec<CollisionEvent>,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn unload(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Err(SceneError::SceneAlreadyLoaded)?
+    }
+  }
+
+  /// A scene that does nothing.
+  struct Empty;
+
+  impl Scene for Empty {
+    fn load(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn frame(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn postframe(
+      &mut self,
+      _: &mut CommandQueue,
+      _: &mut Context,
+      _: Vec<CollisionEvent>,
+    ) -> Result<(), EngineError> {
+      Ok(())
+    }
+    fn unload(&mut self, _: &mut CommandQueue, _: &mut Context) -> Result<(), EngineError> {
+      Ok(())
+    }
+  }
+
+  /// Create a scene manager with the test scenes, and a headless context.
+  fn setup() -> (Scenes, CommandQueue, Context) {
+    let scenes = Scenes::new()
+      .register(Spawner)
+      .register(Carrier)
+      .register(Stubborn)
+      .register(Empty);
+    (scenes, CommandQueue::new(), Context::headless().unwrap())
+  }
+
+  #[test]
+  fn rejected_payload_keeps_the_loaded_scene() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Spawner>(&mut command_queue, &mut context)
+      .unwrap();
+    let result = scenes.load_with::<Empty>(Payload::new(1), &mut command_queue, &mut context);
+    assert!(result.is_err());
+    assert_eq!(scenes.depth(), 1);
+    scenes.frame(&mut command_queue, &mut context).unwrap();
+    assert_eq!(context.world.len(), 1);
+  }
+
+  #[test]
+  fn rejected_payload_keeps_the_scene_unpaused() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Spawner>(&mut command_queue, &mut context)
+      .unwrap();
+    let result = scenes.push_with::<Empty>(Payload::new(1), &mut command_queue, &mut context);
+    assert!(result.is_err());
+    assert_eq!(scenes.depth(), 1);
+  }
+
+  #[test]
+  fn preload_switches_to_a_scene_without_a_receive() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Spawner>(&mut command_queue, &mut context)
+      .unwrap();
+    scenes.preload::<Empty>(AssetManifest::new()).unwrap();
+    scenes
+      .update_preload(&mut command_queue, &mut context)
+      .unwrap();
+    assert!(!scenes.is_preloading());
+    assert_eq!(scenes.depth(), 1);
+    scenes.frame(&mut command_queue, &mut context).unwrap();
+    assert_eq!(context.world.len(), 0);
+  }
+
+  #[test]
+  fn deferred_spawns_are_owned_and_unloaded() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Spawner>(&mut command_queue, &mut context)
+      .unwrap();
+    scenes.frame(&mut command_queue, &mut context).unwrap();
+    let owners = context
+      .world
+      .standard_query::<&Owner>()
+      .into_iter()
+      .map(|(_, owner)| owner.scene)
+      .collect::<Vec<_>>();
+    assert_eq!(owners, vec![TypeId::of::<Spawner>()]);
+    scenes
+      .load::<Empty>(&mut command_queue, &mut context)
+      .unwrap();
+    assert_eq!(context.world.len(), 0);
+  }
+
+  #[test]
+  fn persistent_children_of_owned_entities_carry_over() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Carrier>(&mut command_queue, &mut context)
+      .unwrap();
+    context.world.propagate_transforms();
+    scenes
+      .load::<Empty>(&mut command_queue, &mut context)
+      .unwrap();
+    assert_eq!(context.world.len(), 1);
+    let entity = context.world.entities().next().unwrap();
+    assert_eq!(context.world.parent(entity), None);
+    let transform = context
+      .world
+      .standard_inspect::<&Transform>(entity)
+      .unwrap();
+    assert_eq!(transform.position, Point::new(15.0, 0.0));
+  }
+
+  #[test]
+  fn scenes_that_fail_to_unload_stay_loaded() {
+    let (mut scenes, mut command_queue, mut context) = setup();
+    scenes
+      .load::<Empty>(&mut command_queue, &mut context)
+      .unwrap();
+    scenes
+      .push::<Stubborn>(&mut command_queue, &mut context)
+      .unwrap();
+    assert!(scenes.pop(&mut command_queue, &mut context).is_err());
+    assert_eq!(scenes.depth(), 2);
+    assert!(scenes
+      .load::<Spawner>(&mut command_queue, &mut context)
+      .is_err());
+    assert_eq!(scenes.depth(), 2);
+  }
 }
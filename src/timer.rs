@@ -0,0 +1,337 @@
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// The default fixed timestep, in seconds.
+const DEFAULT_FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// The default maximum number of fixed steps per frame.
+const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 8;
+
+/// Keeps track of time and drives the fixed timestep.
+pub struct Timer {
+  /// The time the timer was created.
+  start: Instant,
+  /// The time of the last tick.
+  last_tick: Instant,
+  /// The number of frames that have been ticked.
+  frames: u64,
+  /// The real time between the last two ticks.
+  delta: Duration,
+  /// Time that has not yet been consumed by fixed steps, in seconds.
+  accumulator: f32,
+  /// The fixed timestep, in seconds.
+  fixed_timestep: f32,
+  /// The rate at which time is accumulated. 1 is real time.
+  time_scale: f32,
+  /// Whether fixed steps are paused.
+  paused: bool,
+  /// Fixed steps queued to be taken while paused.
+  queued_steps: u32,
+  /// The maximum number of fixed steps per frame.
+  max_steps_per_frame: u32,
+  /// The number of fixed steps taken in the current frame.
+  frame_steps: u32,
+  /// The exact number of fixed steps to take in the current frame,
+  /// regardless of the time accumulated.
+  forced_steps: Option<u32>,
+  /// The number of fixed steps taken.
+  steps: u64,
+}
+
+impl Default for Timer {
+  fn default() -> Self {
+    let now = Instant::now();
+    Self {
+      start: now,
+      last_tick: now,
+      frames: 0,
+      delta: Duration::ZERO,
+      accumulator: 0.0,
+      fixed_timestep: DEFAULT_FIXED_TIMESTEP,
+      time_scale: 1.0,
+      paused: false,
+      queued_steps: 0,
+      max_steps_per_frame: DEFAULT_MAX_STEPS_PER_FRAME,
+      frame_steps: 0,
+      forced_steps: None,
+      steps: 0,
+    }
+  }
+}
+
+impl Timer {
+  /// Create a new timer.
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Tick the timer, advancing it by the real time elapsed since the
+  /// last tick.
+  pub fn tick(&mut self) {
+    let now = Instant::now();
+    let delta = now - self.last_tick;
+    self.last_tick = now;
+    self.advance(delta);
+  }
+  /// Tick the timer, advancing it by exactly one fixed timestep
+  /// regardless of the real time elapsed.
+  pub fn tick_fixed(&mut self) {
+    let delta = Duration::from_secs_f32(self.fixed_timestep);
+    self.last_tick = Instant::now();
+    self.advance_by(delta, self.fixed_timestep);
+  }
+  /// Advance the timer by `delta`, starting a new frame.
+  pub fn advance(&mut self, delta: Duration) {
+    self.advance_by(delta, delta.as_secs_f32());
+  }
+  /// Advance the timer by `delta`, which is `seconds` long, starting a new
+  /// frame. The seconds are passed separately so a fixed timestep isn't
+  /// rounded to whole nanoseconds.
+  fn advance_by(&mut self, delta: Duration, seconds: f32) {
+    self.frames += 1;
+    self.delta = delta;
+    self.frame_steps = 0;
+    self.forced_steps = None;
+    // While paused, time is not accumulated and only the queued steps
+    // are taken.
+    if self.paused {
+      return;
+    }
+    self.queued_steps = 0;
+    self.accumulator += seconds * self.time_scale;
+    // Drop the time that can't be simulated this frame. Otherwise, a slow
+    // frame causes more steps, which causes slower frames, and so on.
+    let max_accumulated = self.fixed_timestep * self.max_steps_per_frame as f32;
+    if self.accumulator > max_accumulated {
+      self.accumulator = max_accumulated;
+    }
+  }
+  /// Consume a fixed step of the current frame.
+  /// Returns false if there are no steps remaining.
+  pub fn step(&mut self) -> bool {
+    if let Some(forced_steps) = self.forced_steps {
+      // Take exactly the forced steps.
+      if self.frame_steps >= forced_steps {
+        return false;
+      }
+      self.accumulator = (self.accumulator - self.fixed_timestep).max(0.0);
+    } else if self.paused {
+      // Only take queued steps while paused.
+      if self.queued_steps == 0 {
+        return false;
+      }
+      self.queued_steps -= 1;
+    } else {
+      // Take a step if enough time has accumulated, up to the maximum
+      // number of steps per frame.
+      if self.accumulator < self.fixed_timestep || self.frame_steps >= self.max_steps_per_frame {
+        return false;
+      }
+      self.accumulator -= self.fixed_timestep;
+    }
+    self.frame_steps += 1;
+    self.steps += 1;
+    true
+  }
+  /// Get the interpolation alpha, which is how far the current time is
+  /// between the last fixed step and the next one. Ranges from 0 to 1.
+  pub fn alpha(&self) -> f32 {
+    (self.accumulator / self.fixed_timestep).clamp(0.0, 1.0)
+  }
+  /// Get the time elapsed since the timer was created.
+  pub fn elapsed(&self) -> Duration {
+    self.start.elapsed()
+  }
+  /// Get the time the timer was created.
+  pub fn start(&self) -> Instant {
+    self.start
+  }
+  /// Get the number of frames that have been ticked.
+  pub fn frames(&self) -> u64 {
+    self.frames
+  }
+  /// Get the number of fixed steps taken.
+  pub fn steps(&self) -> u64 {
+    self.steps
+  }
+  /// Get the number of fixed steps taken in the current frame.
+  pub fn frame_steps(&self) -> u32 {
+    self.frame_steps
+  }
+  /// Force the current frame to take exactly `steps` fixed steps,
+  /// regardless of the time accumulated or whether the timer is paused.
+  /// Used to replay recordings.
+  pub fn force_steps(&mut self, steps: u32) {
+    self.forced_steps.replace(steps);
+  }
+  /// Get the real time between the last two ticks.
+  pub fn delta(&self) -> Duration {
+    self.delta
+  }
+  /// Get the fixed timestep, in seconds.
+  pub fn fixed_timestep(&self) -> f32 {
+    self.fixed_timestep
+  }
+  /// Set the fixed timestep, in seconds. It must be positive and finite.
+  pub fn set_fixed_timestep(&mut self, fixed_timestep: f32) -> Result<(), TimerError> {
+    if !fixed_timestep.is_finite() || fixed_timestep <= 0.0 {
+      Err(TimerError::InvalidFixedTimestep(fixed_timestep))?;
+    }
+    self.fixed_timestep = fixed_timestep;
+    Ok(())
+  }
+  /// Get the time scale. 1 is real time.
+  pub fn time_scale(&self) -> f32 {
+    self.time_scale
+  }
+  /// Set the time scale. Values below 1 slow the simulation down and
+  /// values above 1 speed it up.
+  pub fn set_time_scale(&mut self, time_scale: f32) {
+    self.time_scale = time_scale.max(0.0);
+  }
+  /// Get whether fixed steps are paused.
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+  /// Pause fixed steps.
+  pub fn pause(&mut self) {
+    self.paused = true;
+  }
+  /// Resume fixed steps.
+  pub fn resume(&mut self) {
+    self.paused = false;
+  }
+  /// Queue fixed steps to be taken while paused.
+  /// This has no effect if the timer is not paused.
+  pub fn queue_steps(&mut self, steps: u32) {
+    if self.paused {
+      self.queued_steps = self.queued_steps.saturating_add(steps);
+    }
+  }
+  /// Get the maximum number of fixed steps per frame.
+  pub fn max_steps_per_frame(&self) -> u32 {
+    self.max_steps_per_frame
+  }
+  /// Set the maximum number of fixed steps per frame.
+  pub fn set_max_steps_per_frame(&mut self, max_steps_per_frame: u32) {
+    self.max_steps_per_frame = max_steps_per_frame;
+  }
+}
+
+/// Timer-related errors.
+#[derive(Error, Debug)]
+pub enum TimerError {
+  #[error("Fixed timestep must be positive and finite, but was {0}")]
+  InvalidFixedTimestep(f32),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Take the steps of the current frame, returning how many were taken.
+  fn take_steps(timer: &mut Timer) -> u32 {
+    let mut steps = 0;
+    while timer.step() {
+      steps += 1;
+    }
+    steps
+  }
+
+  #[test]
+  fn time_accumulates_into_steps() {
+    let mut timer = Timer::new();
+    timer.set_fixed_timestep(0.25).unwrap();
+    timer.advance(Duration::from_millis(100));
+    assert_eq!(take_steps(&mut timer), 0);
+    assert_eq!(timer.alpha(), 0.4);
+    // The leftover time carries over to the next frames.
+    timer.advance(Duration::from_millis(200));
+    assert_eq!(take_steps(&mut timer), 1);
+    timer.advance(Duration::from_millis(550));
+    assert_eq!(take_steps(&mut timer), 2);
+    assert_eq!(timer.frame_steps(), 2);
+    assert_eq!(timer.steps(), 3);
+    assert_eq!(timer.frames(), 3);
+  }
+
+  #[test]
+  fn steps_are_limited_per_frame() {
+    let mut timer = Timer::new();
+    timer.set_fixed_timestep(0.25).unwrap();
+    timer.set_max_steps_per_frame(2);
+    timer.advance(Duration::from_secs(10));
+    assert_eq!(take_steps(&mut timer), 2);
+    // The time that couldn't be simulated was dropped.
+    timer.advance(Duration::ZERO);
+    assert_eq!(take_steps(&mut timer), 0);
+    // Forced steps are taken regardless of the time and limit.
+    timer.force_steps(3);
+    assert_eq!(take_steps(&mut timer), 3);
+    timer.advance(Duration::ZERO);
+    assert_eq!(take_steps(&mut timer), 0);
+  }
+
+  #[test]
+  fn fixed_ticks_take_one_step() {
+    let mut timer = Timer::new();
+    for _ in 0..100 {
+      timer.tick_fixed();
+      assert_eq!(take_steps(&mut timer), 1);
+    }
+    assert_eq!(timer.steps(), 100);
+  }
+
+  #[test]
+  fn paused_timers_only_take_queued_steps() {
+    let mut timer = Timer::new();
+    timer.set_fixed_timestep(0.25).unwrap();
+    timer.pause();
+    timer.advance(Duration::from_secs(1));
+    assert_eq!(take_steps(&mut timer), 0);
+    timer.queue_steps(2);
+    assert_eq!(take_steps(&mut timer), 2);
+    // Queued steps carry over to the next frames while paused.
+    timer.queue_steps(1);
+    timer.advance(Duration::from_secs(1));
+    assert_eq!(take_steps(&mut timer), 1);
+    // Time isn't accumulated while paused, and steps left queued are
+    // dropped when resumed.
+    timer.queue_steps(1);
+    timer.resume();
+    timer.advance(Duration::ZERO);
+    assert_eq!(take_steps(&mut timer), 0);
+    // Steps aren't queued while running, and the queue saturates.
+    timer.queue_steps(1);
+    timer.pause();
+    timer.advance(Duration::ZERO);
+    assert_eq!(take_steps(&mut timer), 0);
+    timer.queue_steps(u32::MAX);
+    timer.queue_steps(u32::MAX);
+    assert!(timer.step());
+  }
+
+  #[test]
+  fn time_is_scaled() {
+    let mut timer = Timer::new();
+    timer.set_fixed_timestep(0.25).unwrap();
+    timer.set_time_scale(0.5);
+    timer.advance(Duration::from_secs(1));
+    assert_eq!(take_steps(&mut timer), 2);
+    timer.set_time_scale(2.0);
+    timer.advance(Duration::from_millis(500));
+    assert_eq!(take_steps(&mut timer), 4);
+  }
+
+  #[test]
+  fn invalid_fixed_timesteps_are_rejected() {
+    let mut timer = Timer::new();
+    for fixed_timestep in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+      assert!(timer.set_fixed_timestep(fixed_timestep).is_err());
+    }
+    assert_eq!(timer.fixed_timestep(), DEFAULT_FIXED_TIMESTEP);
+    timer.tick_fixed();
+    assert!(timer.alpha().is_finite());
+    assert!(timer.set_fixed_timestep(0.5).is_ok());
+    assert_eq!(timer.fixed_timestep(), 0.5);
+  }
+}
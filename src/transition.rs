@@ -0,0 +1,201 @@
+use crate::{Color, Overlay, Payload};
+use std::any::TypeId;
+
+/// The direction a transition moves in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransitionDirection {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
+impl TransitionDirection {
+  /// Get the direction as a unit vector, where up is positive.
+  fn unit(self) -> [f32; 2] {
+    match self {
+      Self::Left => [-1.0, 0.0],
+      Self::Right => [1.0, 0.0],
+      Self::Up => [0.0, 1.0],
+      Self::Down => [0.0, -1.0],
+    }
+  }
+}
+
+/// An animated transition between scenes. Durations are in seconds of real
+/// time.
+#[derive(Clone, Copy)]
+pub enum Transition {
+  /// Fade the old scene out to a color, then fade the new scene in from it.
+  Fade { color: Color, duration: f32 },
+  /// Fade the old scene out while the new scene fades in.
+  Crossfade { duration: f32 },
+  /// Slide the old scene off the frame, revealing the new scene.
+  Slide {
+    direction: TransitionDirection,
+    duration: f32,
+  },
+  /// Wipe the old scene away with an edge moving across the frame,
+  /// revealing the new scene.
+  Wipe {
+    direction: TransitionDirection,
+    duration: f32,
+  },
+}
+
+impl Transition {
+  /// Get whether the transition composites a capture of the old scene over
+  /// the new scene.
+  fn uses_capture(&self) -> bool {
+    !matches!(self, Self::Fade { .. })
+  }
+  /// Get the duration of the phase in which the new scene is shown.
+  fn in_duration(&self) -> f32 {
+    match *self {
+      Self::Fade { duration, .. } => duration / 2.0,
+      Self::Crossfade { duration } => duration,
+      Self::Slide { duration, .. } => duration,
+      Self::Wipe { duration, .. } => duration,
+    }
+  }
+  /// Get the overlay of the phase in which the new scene is shown.
+  /// `progress` ranges from 0 to 1.
+  fn in_overlay(&self, progress: f32) -> Overlay {
+    let full = [0.0, 0.0, 1.0, 1.0];
+    match *self {
+      Self::Fade { color, .. } => Overlay::Color(color.with_alpha(1.0 - progress)),
+      Self::Crossfade { .. } => Overlay::Capture {
+        color: Color::alpha(1.0 - progress),
+        offset: [0.0, 0.0],
+        visible: full,
+      },
+      Self::Slide { direction, .. } => {
+        let [x, y] = direction.unit();
+        Overlay::Capture {
+          color: Color::none(),
+          offset: [x * progress, y * progress],
+          visible: full,
+        }
+      },
+      Self::Wipe { direction, .. } => {
+        // The edge moves in the direction, so the old scene remains visible
+        // on the side the edge is moving towards.
+        let remaining = 1.0 - progress;
+        let visible = match direction {
+          TransitionDirection::Left => [0.0, 0.0, remaining, 1.0],
+          TransitionDirection::Right => [progress, 0.0, remaining, 1.0],
+          TransitionDirection::Up => [0.0, progress, 1.0, remaining],
+          TransitionDirection::Down => [0.0, 0.0, 1.0, remaining],
+        };
+        Overlay::Capture {
+          color: Color::none(),
+          offset: [0.0, 0.0],
+          visible: visible,
+        }
+      },
+    }
+  }
+}
+
+/// The phase of a transition in progress.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TransitionPhase {
+  /// The old scene is fading out. Only used by `Transition::Fade`.
+  Out,
+  /// The old scene is being captured.
+  Capture,
+  /// The new scene is loaded and being revealed.
+  In,
+}
+
+/// A transition in progress.
+pub struct ActiveTransition {
+  /// The transition.
+  transition: Transition,
+  /// The type id of the scene being transitioned to.
+  target: TypeId,
+  /// The payload to load the scene with, taken once it is loaded.
+  payload: Option<Payload>,
+  /// The current phase.
+  phase: TransitionPhase,
+  /// The time elapsed in the current phase, in seconds.
+  elapsed: f32,
+}
+
+/// What to do after advancing a transition.
+pub enum TransitionStep {
+  /// Draw an overlay.
+  Overlay(Overlay),
+  /// Unload the old scene and load the target scene with the payload, then
+  /// draw an overlay.
+  Switch(TypeId, Option<Payload>, Overlay),
+  /// The transition is finished.
+  Finished,
+}
+
+impl ActiveTransition {
+  /// Start a new transition.
+  pub fn new(transition: Transition, target: TypeId, payload: Option<Payload>) -> Self {
+    let phase = if transition.uses_capture() {
+      TransitionPhase::Capture
+    } else {
+      TransitionPhase::Out
+    };
+    Self {
+      transition: transition,
+      target: target,
+      payload: payload,
+      phase: phase,
+      elapsed: 0.0,
+    }
+  }
+  /// Get the transition.
+  pub fn transition(&self) -> Transition {
+    self.transition
+  }
+  /// Get the current phase.
+  pub fn phase(&self) -> TransitionPhase {
+    self.phase
+  }
+  /// Advance the transition by `delta` seconds.
+  pub fn advance(&mut self, delta: f32) -> TransitionStep {
+    self.elapsed += delta;
+    match self.phase {
+      TransitionPhase::Out => {
+        let progress = progress(self.elapsed, self.transition.in_duration());
+        if progress < 1.0 {
+          let Transition::Fade { color, .. } = self.transition else {
+            unreachable!("only fades have an out phase");
+          };
+          return TransitionStep::Overlay(Overlay::Color(color.with_alpha(progress)));
+        }
+        self.switch()
+      },
+      TransitionPhase::Capture => self.switch(),
+      TransitionPhase::In => {
+        let progress = progress(self.elapsed, self.transition.in_duration());
+        if progress < 1.0 {
+          TransitionStep::Overlay(self.transition.in_overlay(progress))
+        } else {
+          TransitionStep::Finished
+        }
+      },
+    }
+  }
+  /// Move to the in phase, switching scenes.
+  fn switch(&mut self) -> TransitionStep {
+    self.phase = TransitionPhase::In;
+    self.elapsed = 0.0;
+    let overlay = self.transition.in_overlay(0.0);
+    TransitionStep::Switch(self.target, self.payload.take(), overlay)
+  }
+}
+
+/// Get the progress through a phase, from 0 to 1.
+fn progress(elapsed: f32, duration: f32) -> f32 {
+  if duration > 0.0 {
+    (elapsed / duration).min(1.0)
+  } else {
+    1.0
+  }
+}